@@ -0,0 +1,59 @@
+//! Measures encoding, decoding and validating ULIDs, without any dependencies.
+//!
+//! Run with `cargo bench --bench base32`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use mr_ulid::Ulid;
+
+/// Number of ULIDs per iteration.
+const BATCH: usize = 1024;
+
+/// Minimum time to measure each function.
+const DURATION: Duration = Duration::from_secs(2);
+
+/// Runs `f` on all inputs repeatedly, and prints the average time per call.
+fn bench<T>(name: &str, inputs: &[T], mut f: impl FnMut(&T)) {
+    let start = Instant::now();
+    let mut calls = 0_u32;
+    let batch = f64::from(u32::try_from(inputs.len()).unwrap());
+
+    while start.elapsed() < DURATION {
+        for input in inputs {
+            f(black_box(input));
+        }
+        calls += 1;
+    }
+
+    let nanos = start.elapsed().as_secs_f64() * 1e9 / f64::from(calls) / batch;
+    println!("{name:<12} {nanos:>8.2} ns");
+}
+
+fn main() {
+    // Deterministic pseudo-random ULIDs (xorshift), to keep the runs comparable
+    let mut state = 0x2545_F491_4F6C_DD1D_u64;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let ulids: Vec<Ulid> = (0..BATCH)
+        .map(|_| Ulid::from_u128(u128::from(next()) << 64 | u128::from(next())).unwrap_or(Ulid::MIN))
+        .collect();
+    let strings: Vec<String> = ulids.iter().map(ToString::to_string).collect();
+
+    bench("encode", &ulids, |ulid| {
+        black_box(ulid.encode());
+    });
+    bench("decode", &strings, |s| {
+        black_box(s.parse::<Ulid>().unwrap());
+    });
+    bench("validate", &strings, |s| {
+        mr_ulid::validate(black_box(s)).unwrap();
+    });
+}
@@ -0,0 +1,341 @@
+//! Derive macro for ULID-backed newtypes, companion to [mr-ulid](https://docs.rs/mr-ulid).
+//!
+//! Use it through the `derive` feature of `mr-ulid`, which re-exports [`UlidNewtype`](macro@UlidNewtype):
+//!
+//! ```toml
+//! [dependencies]
+//! mr-ulid = { version = "3", features = ["derive"] }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{
+    Data, DeriveInput, Fields, LitByteStr, LitStr, Member, meta::ParseNestedMeta, parse_macro_input, parse_quote,
+};
+
+/// Derives `Display`, `Debug`, `FromStr` and conversions for a newtype around `Ulid` or `ZeroableUlid`.
+///
+/// The newtype must be a struct with exactly one field, e.g. `struct OrderId(Ulid)`.
+/// Its behavior is configured by the `#[ulid(...)]` attribute:
+///
+/// - `prefix = "order"` formats and parses the ID as `order_01JB5C84ZBM8QVBE5QRZW6HY89`, like `PrefixedUlid`.
+///   Parsing then fails with `PrefixedUlidError` instead of `Error`. The prefix consists of 1 to 63 lowercase
+///   ASCII letters, digits and underscores, starts with a letter, and doesn't end with an underscore.
+/// - `zeroable` declares the field to be a `ZeroableUlid` instead of a `Ulid`.
+/// - `conversions(...)` selects the conversions to emit, out of:
+///   - `from`: From the `Ulid` (or `ZeroableUlid`) to the newtype.
+///   - `into`: From the newtype to the `Ulid` (or `ZeroableUlid`).
+///   - `u128`: To `u128`, and from `u128` (`TryFrom` unless `zeroable`).
+///   - `bytes`: To `[u8; 16]`, and from `[u8; 16]` (`TryFrom` unless `zeroable`).
+///
+///   Without the `conversions` attribute, `from` and `into` are emitted.
+/// - `serde` emits `Serialize` and `Deserialize` as string, and requires the `serde` feature of `mr-ulid`.
+///
+/// `Debug` is always derived, so don't add it to `#[derive(...)]`. Derive `Clone`, `Copy`, `PartialEq`, `Eq`,
+/// `PartialOrd`, `Ord` and `Hash` as usual.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::{Ulid, UlidNewtype};
+///
+/// #[derive(UlidNewtype, Clone, Copy, PartialEq, Eq, Hash)]
+/// #[ulid(prefix = "order", conversions(from, into, u128))]
+/// struct OrderId(Ulid);
+///
+/// let id = OrderId::from(Ulid::new());
+/// let s = id.to_string();
+/// assert!(s.starts_with("order_"));
+/// assert_eq!(s.parse::<OrderId>()?, id);
+/// # Ok::<(), mr_ulid::PrefixedUlidError>(())
+/// ```
+#[proc_macro_derive(UlidNewtype, attributes(ulid))]
+pub fn derive_ulid_newtype(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Options given by the `#[ulid(...)]` attribute.
+struct Options {
+    prefix: Option<LitStr>,
+    zeroable: bool,
+    conversions: Conversions,
+    serde: bool,
+}
+
+#[allow(clippy::struct_excessive_bools)] // Independent flags
+#[derive(Default)]
+struct Conversions {
+    from: bool,
+    into: bool,
+    u128: bool,
+    bytes: bool,
+}
+
+impl Options {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut options = Self {
+            prefix: None,
+            zeroable: false,
+            conversions: Conversions {
+                from: true,
+                into: true,
+                ..Conversions::default()
+            },
+            serde: false,
+        };
+
+        for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("ulid")) {
+            attr.parse_nested_meta(|meta| options.parse_meta(&meta))?;
+        }
+
+        Ok(options)
+    }
+
+    fn parse_meta(&mut self, meta: &ParseNestedMeta<'_>) -> syn::Result<()> {
+        if meta.path.is_ident("prefix") {
+            self.prefix = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("zeroable") {
+            self.zeroable = true;
+        } else if meta.path.is_ident("serde") {
+            self.serde = true;
+        } else if meta.path.is_ident("conversions") {
+            let conversions = &mut self.conversions;
+            *conversions = Conversions::default();
+            meta.parse_nested_meta(|meta| {
+                let flag = if meta.path.is_ident("from") {
+                    &mut conversions.from
+                } else if meta.path.is_ident("into") {
+                    &mut conversions.into
+                } else if meta.path.is_ident("u128") {
+                    &mut conversions.u128
+                } else if meta.path.is_ident("bytes") {
+                    &mut conversions.bytes
+                } else {
+                    return Err(meta.error("unknown conversion, expected `from`, `into`, `u128` or `bytes`"));
+                };
+                *flag = true;
+                Ok(())
+            })?;
+        } else {
+            return Err(meta.error("unknown attribute, expected `prefix`, `zeroable`, `conversions` or `serde`"));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the single field of the newtype.
+fn field(input: &DeriveInput) -> syn::Result<Member> {
+    let error = || syn::Error::new_spanned(&input.ident, "`UlidNewtype` requires a struct with exactly one field");
+
+    let Data::Struct(data) = &input.data else {
+        return Err(error());
+    };
+    match &data.fields {
+        Fields::Named(fields) if fields.named.len() == 1 => Ok(Member::Named(fields.named[0].ident.clone().unwrap())),
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Ok(Member::from(0)),
+        _ => Err(error()),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let options = Options::parse(input)?;
+    let field = field(input)?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let ulid = if options.zeroable {
+        quote!(::mr_ulid::ZeroableUlid)
+    } else {
+        quote!(::mr_ulid::Ulid)
+    };
+
+    let mut tokens = TokenStream2::new();
+
+    let name_str = name.to_string();
+    tokens.extend(quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_tuple(#name_str).field(&self.#field).finish()
+            }
+        }
+    });
+
+    tokens.extend(expand_strings(&options, &field, &ulid, input));
+    tokens.extend(expand_conversions(&options, &field, &ulid, input));
+    if options.serde {
+        tokens.extend(expand_serde(&options, &field, &ulid, input));
+    }
+
+    Ok(tokens)
+}
+
+fn expand_strings(options: &Options, field: &Member, ulid: &TokenStream2, input: &DeriveInput) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let impl_display = quote!(impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause);
+    let impl_from_str = quote!(impl #impl_generics ::core::str::FromStr for #name #ty_generics #where_clause);
+
+    let Some(prefix) = &options.prefix else {
+        return quote! {
+            #impl_display {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::fmt::Display::fmt(&self.#field, f)
+                }
+            }
+
+            #impl_from_str {
+                type Err = ::mr_ulid::Error;
+                fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                    <#ulid as ::core::str::FromStr>::from_str(s).map(|ulid| Self { #field: ulid })
+                }
+            }
+        };
+    };
+
+    let from_u128 = if options.zeroable {
+        quote!(::core::result::Result::Ok(#ulid::from_u128(n)))
+    } else {
+        quote! {
+            #ulid::from_u128(n).ok_or(::mr_ulid::PrefixedUlidError::InvalidUlid(::mr_ulid::Error::InvalidZero))
+        }
+    };
+
+    // Validated by `mr_ulid` itself, which has the only copy of the rules
+    let prefix_bytes = LitByteStr::new(prefix.value().as_bytes(), prefix.span());
+    let valid_prefix = quote_spanned! {prefix.span()=>
+        const _: () = ::core::assert!(
+            ::mr_ulid::__private::is_valid_prefix(#prefix_bytes, true),
+            "invalid ULID prefix: expected 1 to 63 lowercase ASCII letters, digits and underscores, \
+             starting with a letter and not ending with an underscore",
+        );
+    };
+
+    quote! {
+        #valid_prefix
+
+        #impl_display {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::mr_ulid::__private::fmt_prefixed(#prefix, self.#field.to_u128(), f)
+            }
+        }
+
+        #impl_from_str {
+            type Err = ::mr_ulid::PrefixedUlidError;
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                let n = ::mr_ulid::__private::parse_prefixed(#prefix, s)?;
+                #from_u128.map(|ulid| Self { #field: ulid })
+            }
+        }
+    }
+}
+
+fn expand_conversions(options: &Options, field: &Member, ulid: &TokenStream2, input: &DeriveInput) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let conversions = &options.conversions;
+
+    let mut tokens = TokenStream2::new();
+
+    if conversions.from {
+        tokens.extend(quote! {
+            impl #impl_generics ::core::convert::From<#ulid> for #name #ty_generics #where_clause {
+                fn from(ulid: #ulid) -> Self {
+                    Self { #field: ulid }
+                }
+            }
+        });
+    }
+
+    if conversions.into {
+        tokens.extend(quote! {
+            impl #impl_generics ::core::convert::From<#name #ty_generics> for #ulid #where_clause {
+                fn from(id: #name #ty_generics) -> Self {
+                    id.#field
+                }
+            }
+        });
+    }
+
+    let mut raw = |raw: TokenStream2| {
+        tokens.extend(quote! {
+            impl #impl_generics ::core::convert::From<#name #ty_generics> for #raw #where_clause {
+                fn from(id: #name #ty_generics) -> Self {
+                    <#raw as ::core::convert::From<#ulid>>::from(id.#field)
+                }
+            }
+        });
+        tokens.extend(if options.zeroable {
+            quote! {
+                impl #impl_generics ::core::convert::From<#raw> for #name #ty_generics #where_clause {
+                    fn from(raw: #raw) -> Self {
+                        Self { #field: <#ulid as ::core::convert::From<#raw>>::from(raw) }
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl #impl_generics ::core::convert::TryFrom<#raw> for #name #ty_generics #where_clause {
+                    type Error = ::mr_ulid::Error;
+                    fn try_from(raw: #raw) -> ::core::result::Result<Self, Self::Error> {
+                        <#ulid as ::core::convert::TryFrom<#raw>>::try_from(raw).map(|ulid| Self { #field: ulid })
+                    }
+                }
+            }
+        });
+    };
+
+    if conversions.u128 {
+        raw(quote!(u128));
+    }
+    if conversions.bytes {
+        raw(quote!([u8; 16]));
+    }
+
+    tokens
+}
+
+fn expand_serde(options: &Options, field: &Member, ulid: &TokenStream2, input: &DeriveInput) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let serde = quote!(::mr_ulid::__private::serde);
+
+    // `Deserialize` needs the additional lifetime `'de`
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, parse_quote!('de));
+    let (de_impl_generics, _, _) = generics.split_for_impl();
+
+    let (serialize, deserialize) = if options.prefix.is_some() {
+        (
+            quote!(serializer.collect_str(self)),
+            quote!(::mr_ulid::__private::deserialize_from_str(deserializer)),
+        )
+    } else {
+        (
+            quote!(#serde::Serialize::serialize(&self.#field, serializer)),
+            quote!(<#ulid as #serde::Deserialize<'de>>::deserialize(deserializer).map(|ulid| Self { #field: ulid })),
+        )
+    };
+
+    quote! {
+        impl #impl_generics #serde::Serialize for #name #ty_generics #where_clause {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: #serde::Serializer,
+            {
+                #serialize
+            }
+        }
+
+        impl #de_impl_generics #serde::Deserialize<'de> for #name #ty_generics #where_clause {
+            fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+            where
+                D: #serde::Deserializer<'de>,
+            {
+                #deserialize
+            }
+        }
+    }
+}
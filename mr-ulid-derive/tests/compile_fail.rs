@@ -0,0 +1,9 @@
+/// Checks that invalid uses of `#[derive(UlidNewtype)]` are rejected with a helpful error.
+///
+/// Ignored by default, as the compiler's diagnostics change between Rust versions. CI runs it on stable.
+#[test]
+#[ignore = "compiler diagnostics differ between Rust versions"]
+fn test_compile_fail() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+}
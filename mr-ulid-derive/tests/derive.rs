@@ -0,0 +1,102 @@
+use std::{collections::HashSet, str::FromStr};
+
+use mr_ulid::{Error, PrefixedUlidError, Ulid, UlidNewtype, ZeroableUlid};
+
+// cspell:disable-next-line
+const ULID: &str = "01JBX69S0YBD54BR1ZV3S8PX92";
+
+#[derive(UlidNewtype, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[ulid(serde)]
+struct UserId(Ulid);
+
+#[derive(UlidNewtype, Clone, Copy, PartialEq, Eq)]
+#[ulid(prefix = "order", conversions(from, into, u128, bytes), serde)]
+struct OrderId(Ulid);
+
+#[derive(UlidNewtype, Clone, Copy, PartialEq, Eq)]
+#[ulid(prefix = "cursor", zeroable, conversions(u128, bytes), serde)]
+struct Cursor {
+    position: ZeroableUlid,
+}
+
+#[test]
+fn test_plain() {
+    let id: UserId = ULID.parse().unwrap();
+    let u = Ulid::from(id);
+
+    assert_eq!(id.to_string(), ULID);
+    assert_eq!(format!("{id:#}"), ULID.to_lowercase());
+    assert_eq!(format!("{id:?}"), format!("UserId({u:?})"));
+    assert_eq!(UserId::from(u), id);
+    assert_eq!(
+        UserId::from_str("0"),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 1
+        })
+    );
+    assert_eq!(UserId::from_str(&"0".repeat(26)), Err(Error::InvalidZero));
+
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(json, format!("\"{ULID}\""));
+    assert_eq!(serde_json::from_str::<UserId>(&json).unwrap(), id);
+
+    assert_eq!([id, id].into_iter().collect::<HashSet<_>>().len(), 1);
+}
+
+#[test]
+fn test_prefix() {
+    let s = format!("order_{ULID}");
+    let id: OrderId = s.parse().unwrap();
+    let u = Ulid::from(id);
+
+    assert_eq!(u.to_string(), ULID);
+    assert_eq!(id.to_string(), s);
+    assert_eq!(format!("[{id:>33}]"), format!("[ {s}]"));
+    assert_eq!(OrderId::from(u), id);
+    assert_eq!(u128::from(id), u.to_u128());
+    assert_eq!(OrderId::try_from(u.to_u128()), Ok(id));
+    assert_eq!(OrderId::try_from(0), Err(Error::InvalidZero));
+    assert_eq!(<[u8; 16]>::from(id), u.to_bytes());
+    assert_eq!(OrderId::try_from(u.to_bytes()), Ok(id));
+
+    assert_eq!(
+        OrderId::from_str(ULID),
+        Err(PrefixedUlidError::WrongPrefix { expected: "order" })
+    );
+    assert_eq!(
+        OrderId::from_str(&format!("user_{ULID}")),
+        Err(PrefixedUlidError::WrongPrefix { expected: "order" })
+    );
+    assert_eq!(
+        OrderId::from_str("order_01JBX69S0YBD54BR1ZV3S8PX9U"),
+        Err(PrefixedUlidError::InvalidUlid(Error::InvalidChar {
+            offset: 31,
+            byte: b'U'
+        }))
+    );
+    assert_eq!(
+        OrderId::from_str(&format!("order_{}", "0".repeat(26))),
+        Err(PrefixedUlidError::InvalidUlid(Error::InvalidZero))
+    );
+
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(json, format!("\"{s}\""));
+    assert_eq!(serde_json::from_str::<OrderId>(&json).unwrap(), id);
+    assert!(serde_json::from_str::<OrderId>(&format!("\"{ULID}\"")).is_err());
+}
+
+#[test]
+fn test_zeroable() {
+    let zero = format!("cursor_{}", "0".repeat(26));
+    let cursor: Cursor = zero.parse().unwrap();
+
+    assert_eq!(cursor.position, ZeroableUlid::zeroed());
+    assert_eq!(cursor.to_string(), zero);
+    assert_eq!(Cursor::from(0), cursor);
+    assert_eq!(u128::from(Cursor::from(42)), 42);
+    assert_eq!(Cursor::from([0; 16]), cursor);
+
+    let json = serde_json::to_string(&cursor).unwrap();
+    assert_eq!(serde_json::from_str::<Cursor>(&json).unwrap(), cursor);
+}
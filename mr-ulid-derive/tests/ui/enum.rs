@@ -0,0 +1,9 @@
+use mr_ulid::{Ulid, UlidNewtype};
+
+#[derive(UlidNewtype)]
+enum Id {
+    User(Ulid),
+    Order(Ulid),
+}
+
+fn main() {}
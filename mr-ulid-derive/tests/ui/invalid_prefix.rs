@@ -0,0 +1,11 @@
+use mr_ulid::{Ulid, UlidNewtype};
+
+#[derive(UlidNewtype)]
+#[ulid(prefix = "Order")]
+struct OrderId(Ulid);
+
+#[derive(UlidNewtype)]
+#[ulid(prefix = "order_")]
+struct OrderItemId(Ulid);
+
+fn main() {}
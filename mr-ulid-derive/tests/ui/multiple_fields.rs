@@ -0,0 +1,15 @@
+use mr_ulid::{Ulid, UlidNewtype};
+
+#[derive(UlidNewtype)]
+struct OrderId(Ulid, u32);
+
+#[derive(UlidNewtype)]
+struct Order {
+    id: Ulid,
+    quantity: u32,
+}
+
+#[derive(UlidNewtype)]
+struct Empty;
+
+fn main() {}
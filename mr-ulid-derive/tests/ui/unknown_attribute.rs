@@ -0,0 +1,7 @@
+use mr_ulid::{Ulid, UlidNewtype};
+
+#[derive(UlidNewtype)]
+#[ulid(prefix = "order", display)]
+struct OrderId(Ulid);
+
+fn main() {}
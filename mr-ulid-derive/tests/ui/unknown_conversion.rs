@@ -0,0 +1,7 @@
+use mr_ulid::{Ulid, UlidNewtype};
+
+#[derive(UlidNewtype)]
+#[ulid(conversions(from, string))]
+struct OrderId(Ulid);
+
+fn main() {}
@@ -1,102 +1,280 @@
-use std::str::from_utf8_unchecked;
+use core::str::from_utf8_unchecked;
 
 use crate::Error;
 
-pub fn encode(mut n: u128, buffer: &mut [u8; 26]) -> &str {
-    // cspell:disable-next-line
-    const ALPHABET: [u8; 32] = *b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+mod swar;
 
+#[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+mod neon;
+#[cfg(target_arch = "x86_64")]
+mod x86;
+
+// The fastest implementation available for the target architecture
+#[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+use neon as fast;
+#[cfg(not(any(target_arch = "x86_64", all(target_arch = "aarch64", target_endian = "little"))))]
+use swar as fast;
+#[cfg(target_arch = "x86_64")]
+use x86 as fast;
+
+// cspell:disable-next-line
+const ALPHABET: [u8; 32] = *b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Values of the characters, `-1` for invalid characters.
+#[rustfmt::skip]
+const DECODE: [i8; 256] = [
+    /* 0x00 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0x10 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0x20 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0x30 */   0,  1,  2,  3,  4,  5,  6,  7,  8,  9, -1, -1, -1, -1, -1, -1,
+    /* 0x40 */  -1, 10, 11, 12, 13, 14, 15, 16, 17,  1, 18, 19,  1, 20, 21,  0,
+    /* 0x50 */  22, 23, 24, 25, 26, -1, 27, 28, 29, 30, 31, -1, -1, -1, -1, -1,
+    /* 0x60 */  -1, 10, 11, 12, 13, 14, 15, 16, 17,  1, 18, 19,  1, 20, 21,  0,
+    /* 0x70 */  22, 23, 24, 25, 26, -1, 27, 28, 29, 30, 31, -1, -1, -1, -1, -1,
+    /* 0x80 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0x90 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0xA0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0xB0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0xC0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0xD0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0xE0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+    /* 0xF0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+];
+
+pub fn encode(n: u128, buffer: &mut [u8; 26]) -> &str {
+    fast::encode(n, buffer);
+
+    // Safety: Encoding above guarantees valid UTF-8
+    unsafe { from_utf8_unchecked(buffer) }
+}
+
+/// Reference implementation of [`encode()`].
+#[cfg(test)]
+fn encode_scalar(mut n: u128, buffer: &mut [u8; 26]) {
     for byte in buffer.iter_mut().rev() {
         *byte = ALPHABET[(n & 0x1F) as usize];
         n >>= 5;
     }
+}
 
-    // Safety: Encoding above guarantees valid UTF-8
+pub fn encode_lower(n: u128, buffer: &mut [u8; 26]) -> &str {
+    encode(n, buffer);
+    buffer.make_ascii_lowercase();
+
+    // Safety: Lowercasing the ASCII characters of the encoding keeps valid UTF-8
     unsafe { from_utf8_unchecked(buffer) }
 }
 
 pub fn decode(ascii_bytes: &[u8; 26]) -> Result<u128, Error> {
-    #[rustfmt::skip]
-    const DECODE: [i8; 256] = [
-        /* 0x00 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0x10 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0x20 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0x30 */   0,  1,  2,  3,  4,  5,  6,  7,  8,  9, -1, -1, -1, -1, -1, -1,
-        /* 0x40 */  -1, 10, 11, 12, 13, 14, 15, 16, 17,  1, 18, 19,  1, 20, 21,  0,
-        /* 0x50 */  22, 23, 24, 25, 26, -1, 27, 28, 29, 30, 31, -1, -1, -1, -1, -1,
-        /* 0x60 */  -1, 10, 11, 12, 13, 14, 15, 16, 17,  1, 18, 19,  1, 20, 21,  0,
-        /* 0x70 */  22, 23, 24, 25, 26, -1, 27, 28, 29, 30, 31, -1, -1, -1, -1, -1,
-        /* 0x80 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0x90 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0xA0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0xB0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0xC0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0xD0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0xE0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-        /* 0xF0 */  -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-    ];
-
-    fn decode(char: u8) -> Result<u128, Error> {
-        u128::try_from(DECODE[usize::from(char)]).or(Err(Error::InvalidChar))
+    // The fast paths only tell whether the input is valid, the scalar path pinpoints the error
+    fast::decode(ascii_bytes).map_or_else(|| decode_scalar(ascii_bytes), Ok)
+}
+
+/// Decodes consecutive entries of 26 characters into `values`, returning `false` if any entry is invalid.
+///
+/// The implementation is chosen once for all entries, and their validity is checked once at the end.
+/// The values are unspecified if any entry is invalid.
+pub fn decode_many(input: &[u8], values: &mut [u128]) -> bool {
+    fast::decode_many(input, values)
+}
+
+/// Decodes entries with `decode`, without branching on their validity. Shared by the fast paths.
+fn decode_entries(input: &[u8], values: &mut [u128], decode: impl Fn(&[u8; 26]) -> Option<u128>) -> bool {
+    let mut valid = true;
+    for (entry, value) in input.chunks_exact(26).zip(values) {
+        let decoded = decode(entry.try_into().unwrap());
+        valid &= decoded.is_some();
+        *value = decoded.unwrap_or(0);
     }
+    valid
+}
 
-    let mut n = decode(ascii_bytes[0])?;
+pub fn validate(buffer: &[u8; 26]) -> Result<(), Error> {
+    decode(buffer).map(|_| ())
+}
+
+/// Decodes one character at a time, reporting the first error.
+fn decode_scalar(ascii_bytes: &[u8; 26]) -> Result<u128, Error> {
+    fn decode(offset: usize, byte: u8) -> Result<u128, Error> {
+        u128::try_from(DECODE[usize::from(byte)]).or(Err(Error::InvalidChar { offset, byte }))
+    }
+
+    let mut n = decode(0, ascii_bytes[0])?;
 
     if n <= 7 {
-        for &byte in &ascii_bytes[1..26] {
-            n = (n << 5) | decode(byte)?;
+        for (offset, &byte) in ascii_bytes.iter().enumerate().skip(1) {
+            n = (n << 5) | decode(offset, byte)?;
         }
         Ok(n)
     } else {
-        Err(Error::InvalidChar)
+        Err(Error::Overflow {
+            offset: 0,
+            byte: ascii_bytes[0],
+        })
     }
 }
 
-pub fn validate(buffer: &[u8; 26]) -> Result<(), Error> {
-    let first_ok = is_valid_first_char(buffer[0]);
-    let rest_ok = buffer[1..].iter().all(|&c| is_valid_char(c));
+/// Reference implementation of [`validate()`].
+#[cfg(test)]
+fn validate_scalar(buffer: &[u8; 26]) -> Result<(), Error> {
+    // Report errors in the same order as `decode()`
+    if is_valid_char(buffer[0]) && !is_valid_first_char(buffer[0]) {
+        return Err(Error::Overflow {
+            offset: 0,
+            byte: buffer[0],
+        });
+    }
 
-    if first_ok && rest_ok {
-        Ok(())
-    } else {
-        Err(Error::InvalidChar)
+    if let Some(offset) = buffer.iter().position(|&c| !is_valid_char(c)) {
+        return Err(Error::InvalidChar {
+            offset,
+            byte: buffer[offset],
+        });
     }
+
+    Ok(())
 }
 
+#[cfg(feature = "alloc")]
 pub fn canonicalize(buffer: &mut [u8; 26]) -> Result<&str, Error> {
     buffer[0] = normalize_first_char(buffer[0])?;
 
-    for byte in &mut buffer[1..] {
-        *byte = normalize_char(*byte)?;
+    for (offset, byte) in buffer.iter_mut().enumerate().skip(1) {
+        *byte = normalize_char(offset, *byte)?;
     }
 
     // Safety: Above code guarantees valid UTF-8 (it returns early, when not)
     Ok(unsafe { from_utf8_unchecked(buffer) })
 }
 
+#[cfg(test)]
 const fn is_valid_first_char(c: u8) -> bool {
     matches!(c, b'0'..=b'7' | b'o' | b'i' | b'l' | b'O' | b'I' | b'L')
 }
 
+#[cfg(any(feature = "alloc", test))]
 const fn is_valid_char(c: u8) -> bool {
     c.is_ascii_alphanumeric() && c != b'u' && c != b'U'
 }
 
+#[cfg(feature = "alloc")]
 const fn normalize_first_char(c: u8) -> Result<u8, Error> {
     match c {
         b'0'..=b'7' => Ok(c),
         b'i' | b'I' | b'l' | b'L' => Ok(b'1'),
         b'o' | b'O' => Ok(b'0'),
-        _ => Err(Error::InvalidChar),
+        _ if is_valid_char(c) => Err(Error::Overflow { offset: 0, byte: c }),
+        _ => Err(Error::InvalidChar { offset: 0, byte: c }),
     }
 }
 
-const fn normalize_char(c: u8) -> Result<u8, Error> {
+#[cfg(feature = "alloc")]
+const fn normalize_char(offset: usize, c: u8) -> Result<u8, Error> {
     match c {
         b'i' | b'I' | b'l' | b'L' => Ok(b'1'),
         b'o' | b'O' => Ok(b'0'),
-        b'u' | b'U' => Err(Error::InvalidChar),
+        b'u' | b'U' => Err(Error::InvalidChar { offset, byte: c }),
         other if other.is_ascii_alphanumeric() => Ok(other.to_ascii_uppercase()),
-        _ => Err(Error::InvalidChar),
+        _ => Err(Error::InvalidChar { offset, byte: c }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random numbers (xorshift), to keep the tests reproducible.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn next_u128(&mut self) -> u128 {
+            u128::from(self.next()) << 64 | u128::from(self.next())
+        }
+    }
+
+    /// Asserts that all implementations agree on decoding and validating `input`.
+    fn assert_decode(input: &[u8; 26]) {
+        let expected = decode_scalar(input);
+
+        assert_eq!(decode(input), expected, "input: {input:?}");
+        let mut value = [0];
+        assert_eq!(decode_many(input, &mut value), expected.is_ok(), "input: {input:?}");
+        assert!(expected.is_err() || expected == Ok(value[0]), "input: {input:?}");
+        for result in fast::decode_each(input) {
+            assert_eq!(result, expected.ok(), "input: {input:?}");
+        }
+        assert_eq!(validate(input), validate_scalar(input), "input: {input:?}");
+        assert_eq!(validate(input), expected.map(|_| ()), "input: {input:?}");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+
+        let edges = (0..128)
+            .flat_map(|bit| [1 << bit, (1 << bit) - 1])
+            .chain([0, u128::MAX]);
+        let random = (0..10_000).map(|_| rng.next_u128());
+
+        for n in edges.chain(random) {
+            let mut expected = [0; 26];
+            encode_scalar(n, &mut expected);
+            for result in fast::encode_each(n) {
+                assert_eq!(result, expected, "n: {n}");
+            }
+
+            let mut buffer = [0; 26];
+            encode(n, &mut buffer);
+            assert_eq!(buffer, expected);
+            assert_decode(&buffer);
+            assert_eq!(decode(&buffer), Ok(n));
+        }
+    }
+
+    #[test]
+    fn test_decode_differential_single_byte() {
+        // cspell:disable-next-line
+        let bases = [
+            *b"00000000000000000000000000",
+            *b"7ZZZZZZZZZZZZZZZZZZZZZZZZZ",
+            *b"01jb5c84zbm8qvbe5qrzw6hy89",
+        ];
+
+        for base in bases {
+            for offset in 0..26 {
+                for byte in 0..=255 {
+                    let mut input = base;
+                    input[offset] = byte;
+                    assert_decode(&input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_differential_random() {
+        // cspell:disable-next-line
+        const CHARS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+
+        for _ in 0..10_000 {
+            // Mostly valid characters, occasionally any byte
+            let input = [0; 26].map(|_: u8| {
+                let r = rng.next();
+                if r % 64 == 0 {
+                    (r >> 8).to_le_bytes()[0]
+                } else {
+                    CHARS[(r >> 8) as usize % CHARS.len()]
+                }
+            });
+            assert_decode(&input);
+        }
     }
 }
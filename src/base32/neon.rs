@@ -0,0 +1,147 @@
+//! Decoding and encoding with NEON, which every `aarch64` CPU has.
+//!
+//! Characters are mapped to their values by looking up their low nibble with `tbl` in the rows of the
+//! decoding table for digits, `A`-`O` and `P`-`Z`, chosen by their high nibble (ignoring case).
+//! Pairs of values are then shifted and combined into 10 bits, and pairs of those into 20 bits,
+//! leaving only eight words to join.
+//!
+//! The 26 characters are loaded and stored as two overlapping vectors of 16 characters, as copying them into
+//! a padded buffer first costs more than the vectors save (the wide load can't forward the narrower stores).
+
+use core::arch::aarch64::{
+    uint8x16_t, uint32x4_t, vandq_u8, vandq_u16, vandq_u32, vceqq_u8, vcombine_u64, vcreate_u64, vdupq_n_u8,
+    vdupq_n_u16, vdupq_n_u32, vextq_u8, vld1q_u8, vld1q_u8_x2, vmaxvq_u8, vmvnq_u8, vorrq_u8, vorrq_u16, vorrq_u32,
+    vqtbl1q_u8, vqtbl2q_u8, vreinterpretq_u8_u64, vreinterpretq_u16_u8, vreinterpretq_u32_u16, vshlq_n_u16,
+    vshlq_n_u32, vshrq_n_u8, vshrq_n_u16, vshrq_n_u32, vst1q_u8, vst1q_u32,
+};
+
+use super::{ALPHABET, DECODE, decode_entries, swar};
+
+/// Decodes 26 characters, returning `None` if they are no valid ULID.
+pub fn decode(bytes: &[u8; 26]) -> Option<u128> {
+    // Safety: NEON is always available on `aarch64`
+    unsafe { decode_neon(bytes) }
+}
+
+/// Decodes consecutive entries of 26 characters into `values`, returning `false` if any entry is invalid.
+pub fn decode_many(input: &[u8], values: &mut [u128]) -> bool {
+    decode_entries(input, values, decode)
+}
+
+/// Encodes into 26 characters.
+pub fn encode(n: u128, buffer: &mut [u8; 26]) {
+    // Safety: NEON is always available on `aarch64`
+    unsafe { encode_neon(n, buffer) }
+}
+
+/// Results of all implementations available, for differential tests.
+#[cfg(test)]
+pub fn decode_each(bytes: &[u8; 26]) -> Vec<Option<u128>> {
+    let mut results = swar::decode_each(bytes);
+    results.push(decode(bytes));
+    results
+}
+
+/// Results of all implementations available, for differential tests.
+#[cfg(test)]
+pub fn encode_each(n: u128) -> Vec<[u8; 26]> {
+    let mut results = swar::encode_each(n);
+    let mut buffer = [0; 26];
+    encode(n, &mut buffer);
+    results.push(buffer);
+    results
+}
+
+// Intrinsics without pointers are safe to call since Rust 1.87, but not in the supported Rust 1.85
+
+#[target_feature(enable = "neon")]
+#[allow(unused_unsafe)]
+unsafe fn decode_neon(bytes: &[u8; 26]) -> Option<u128> {
+    let mut words = [0_u32; 8];
+
+    // Safety: The loads and stores stay within the arrays, which need no alignment
+    unsafe {
+        let first = values(vld1q_u8(bytes.as_ptr()));
+        let last = values(vld1q_u8(bytes[10..].as_ptr()));
+
+        // Invalid characters have the high bit set
+        if vmaxvq_u8(vorrq_u8(first, last)) >= 0x80 {
+            return None;
+        }
+
+        // Put the first 10 values behind 6 padding zeros, followed by the last 16 values
+        let first = vextq_u8::<10>(vdupq_n_u8(0), first);
+
+        vst1q_u32(words.as_mut_ptr(), pack(first));
+        vst1q_u32(words[4..].as_mut_ptr(), pack(last));
+    }
+
+    swar::join_words(words)
+}
+
+#[target_feature(enable = "neon")]
+#[allow(unused_unsafe)]
+unsafe fn encode_neon(n: u128, buffer: &mut [u8; 26]) {
+    let [g0, g1, g2, g3] = swar::encode_values(n);
+
+    // Safety: The loads and stores stay within the arrays, which need no alignment
+    unsafe {
+        let table = vld1q_u8_x2(ALPHABET.as_ptr());
+        let first = vqtbl2q_u8(
+            table,
+            vreinterpretq_u8_u64(vcombine_u64(vcreate_u64(g0), vcreate_u64(g1))),
+        );
+        let last = vqtbl2q_u8(
+            table,
+            vreinterpretq_u8_u64(vcombine_u64(vcreate_u64(g2), vcreate_u64(g3))),
+        );
+
+        // Drop the 6 padding characters, the last 16 characters overwrite the zeros shifted in
+        vst1q_u8(buffer.as_mut_ptr(), vextq_u8::<6>(first, vdupq_n_u8(0)));
+        vst1q_u8(buffer[10..].as_mut_ptr(), last);
+    }
+}
+
+/// Maps 16 characters to their values `0..=31`, and invalid characters to `0xFF`.
+#[target_feature(enable = "neon")]
+#[allow(unused_unsafe)]
+unsafe fn values(chars: uint8x16_t) -> uint8x16_t {
+    // Safety: The loads stay within the decoding table, which needs no alignment
+    unsafe {
+        let low = vandq_u8(chars, vdupq_n_u8(0x0F));
+        let high = vshrq_n_u8::<4>(chars);
+
+        // Setting bit 1 of the high nibble folds lowercase into uppercase letters
+        let folded = vorrq_u8(high, vdupq_n_u8(0x02));
+        let is_digit = vceqq_u8(high, vdupq_n_u8(0x03));
+        let is_a_to_o = vceqq_u8(folded, vdupq_n_u8(0x06));
+        let is_p_to_z = vceqq_u8(folded, vdupq_n_u8(0x07));
+
+        let digits = vqtbl1q_u8(vld1q_u8(DECODE[0x30..].as_ptr().cast()), low);
+        let a_to_o = vqtbl1q_u8(vld1q_u8(DECODE[0x40..].as_ptr().cast()), low);
+        let p_to_z = vqtbl1q_u8(vld1q_u8(DECODE[0x50..].as_ptr().cast()), low);
+
+        let invalid = vmvnq_u8(vorrq_u8(vorrq_u8(is_digit, is_a_to_o), is_p_to_z));
+
+        vorrq_u8(
+            vorrq_u8(vandq_u8(is_digit, digits), vandq_u8(is_a_to_o, a_to_o)),
+            vorrq_u8(vandq_u8(is_p_to_z, p_to_z), invalid),
+        )
+    }
+}
+
+/// Packs 16 values of 5 bits into four words of 20 bits, the first values into the most significant bits.
+#[target_feature(enable = "neon")]
+#[allow(unused_unsafe)]
+unsafe fn pack(values: uint8x16_t) -> uint32x4_t {
+    // Safety: No memory is accessed
+    unsafe {
+        let v = vreinterpretq_u16_u8(values);
+        let v = vorrq_u16(vshlq_n_u16::<5>(vandq_u16(v, vdupq_n_u16(0x00FF))), vshrq_n_u16::<8>(v));
+        let v = vreinterpretq_u32_u16(v);
+        vorrq_u32(
+            vshlq_n_u32::<10>(vandq_u32(v, vdupq_n_u32(0xFFFF))),
+            vshrq_n_u32::<16>(v),
+        )
+    }
+}
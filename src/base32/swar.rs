@@ -0,0 +1,141 @@
+//! Decoding and encoding with SWAR (SIMD within a register).
+//!
+//! The 26 characters of a ULID are padded with 6 leading zeros to 32 characters, i.e. four words of
+//! 8 characters. The values of each word are gathered into a `u64`, checked for invalid characters at once,
+//! and packed into 40 bits of the padded 160-bit value, without a long dependency chain of 128-bit shifts.
+//! Encoding reverses these steps and computes the characters arithmetically instead of looking them up.
+
+use super::{DECODE, decode_entries};
+
+/// Every byte set to `0x01`.
+const ONES: u64 = 0x0101_0101_0101_0101;
+
+/// Every byte set to `0x80`.
+const HIGH: u64 = 0x8080_8080_8080_8080;
+
+/// Decodes 26 characters, returning `None` if they are no valid ULID.
+pub fn decode(bytes: &[u8; 26]) -> Option<u128> {
+    let padded = pad(bytes);
+    let mut groups = [0; 4];
+
+    for (group, chunk) in groups.iter_mut().zip(padded.chunks_exact(8)) {
+        *group = pack(values(chunk)?);
+    }
+
+    join(groups)
+}
+
+/// Decodes consecutive entries of 26 characters into `values`, returning `false` if any entry is invalid.
+pub fn decode_many(input: &[u8], values: &mut [u128]) -> bool {
+    decode_entries(input, values, decode)
+}
+
+/// Encodes into 26 characters.
+pub fn encode(n: u128, buffer: &mut [u8; 26]) {
+    let [g0, g1, g2, g3] = encode_values(n).map(|values| ascii(values).to_le_bytes());
+
+    // The first group holds 6 characters of padding
+    buffer[..2].copy_from_slice(&g0[6..]);
+    buffer[2..10].copy_from_slice(&g1);
+    buffer[10..18].copy_from_slice(&g2);
+    buffer[18..].copy_from_slice(&g3);
+}
+
+/// Splits into the values `0..=31` of 32 characters in four words, the first 6 being padding zeros.
+/// Used by the SIMD paths.
+pub fn encode_values(n: u128) -> [u64; 4] {
+    split(n).map(unpack)
+}
+
+/// Results of all implementations available, for differential tests.
+#[cfg(test)]
+pub fn decode_each(bytes: &[u8; 26]) -> Vec<Option<u128>> {
+    vec![decode(bytes)]
+}
+
+/// Results of all implementations available, for differential tests.
+#[cfg(test)]
+pub fn encode_each(n: u128) -> Vec<[u8; 26]> {
+    let mut buffer = [0; 26];
+    encode(n, &mut buffer);
+    vec![buffer]
+}
+
+/// Pads the 26 characters with 6 leading zeros to 32 characters.
+fn pad(bytes: &[u8; 26]) -> [u8; 32] {
+    let mut padded = [b'0'; 32];
+    padded[6..].copy_from_slice(bytes);
+    padded
+}
+
+/// Joins four groups of 40 bits, most significant first, into a `u128`.
+///
+/// Returns `None` if the value doesn't fit, i.e. the first character was above `'7'`.
+fn join([g0, g1, g2, g3]: [u64; 4]) -> Option<u128> {
+    if g0 > 0xFF {
+        return None;
+    }
+    Some(u128::from(g0) << 120 | u128::from(g1) << 80 | u128::from(g2) << 40 | u128::from(g3))
+}
+
+/// Joins eight words of 20 bits, most significant first, into a `u128`. Used by the SIMD paths.
+///
+/// Returns `None` if the value doesn't fit, i.e. the first character was above `'7'`.
+pub fn join_words(words: [u32; 8]) -> Option<u128> {
+    let group = |i: usize| u64::from(words[2 * i]) << 20 | u64::from(words[2 * i + 1]);
+    join([group(0), group(1), group(2), group(3)])
+}
+
+/// Splits into four groups of 40 bits, most significant first, i.e. the inverse of [`join()`].
+#[allow(clippy::cast_possible_truncation)]
+const fn split(n: u128) -> [u64; 4] {
+    const MASK: u128 = (1 << 40) - 1;
+    [
+        (n >> 120) as u64,
+        (n >> 80 & MASK) as u64,
+        (n >> 40 & MASK) as u64,
+        (n & MASK) as u64,
+    ]
+}
+
+/// Packs 8 bytes of 5 bits each into 40 bits, the first (lowest) byte into the most significant bits.
+const fn pack(v: u64) -> u64 {
+    let v = ((v & 0x00FF_00FF_00FF_00FF) << 5) | ((v >> 8) & 0x00FF_00FF_00FF_00FF);
+    let v = ((v & 0x0000_FFFF_0000_FFFF) << 10) | ((v >> 16) & 0x0000_FFFF_0000_FFFF);
+    ((v & 0xFFFF_FFFF) << 20) | (v >> 32)
+}
+
+/// Unpacks 40 bits into 8 bytes of 5 bits each, i.e. the inverse of [`pack()`].
+const fn unpack(v: u64) -> u64 {
+    let v = (v >> 20) | ((v & 0xF_FFFF) << 32);
+    let v = ((v >> 10) & 0x0000_03FF_0000_03FF) | ((v & 0x0000_03FF_0000_03FF) << 16);
+    ((v >> 5) & 0x001F_001F_001F_001F) | ((v & 0x001F_001F_001F_001F) << 8)
+}
+
+/// Maps 8 characters to their values `0..=31`, returning `None` if any character is invalid.
+fn values(chunk: &[u8]) -> Option<u64> {
+    // Invalid characters map to -1, i.e. 0xFF with the high bit set
+    #[allow(clippy::cast_sign_loss)]
+    let values = chunk
+        .iter()
+        .rev()
+        .fold(0, |values, &c| values << 8 | u64::from(DECODE[usize::from(c)] as u8));
+
+    (values & HIGH == 0).then_some(values)
+}
+
+/// Maps 8 values `0..=31` to their characters, i.e. the inverse of [`values()`] for canonical characters.
+const fn ascii(v: u64) -> u64 {
+    /// Sets each byte to 1 if its value is at least `k`, else to 0. Values must be below `0x80`.
+    const fn at_least(v: u64, k: u64) -> u64 {
+        ((v + (0x80 - k) * ONES) >> 7) & ONES
+    }
+
+    // Digits follow `'0'`, letters follow `'A'` skipping `I`, `L`, `O` and `U`
+    v + (b'0' as u64) * ONES
+        + 7 * at_least(v, 10)
+        + at_least(v, 18)
+        + at_least(v, 20)
+        + at_least(v, 22)
+        + at_least(v, 27)
+}
@@ -0,0 +1,314 @@
+//! Decoding and encoding with SSE4.1 and AVX2, falling back to SWAR.
+//!
+//! The instruction set is detected at runtime with `std`, and chosen at compile time without.
+//! Characters are mapped to their values by looking up their low nibble with `pshufb` in the rows of the
+//! decoding table for digits, `A`-`O` and `P`-`Z`, chosen by their high nibble (ignoring case).
+//! Pairs of values are then multiplied and added into 10 bits, and pairs of those into 20 bits,
+//! leaving only eight words to join.
+//!
+//! The 26 characters are loaded and stored as two overlapping vectors of 16 characters, as copying them into
+//! a padded buffer first costs more than the vectors save (the wide load can't forward the narrower stores).
+
+use core::arch::x86_64::{
+    __m128i, __m256i, _mm_and_si128, _mm_andnot_si128, _mm_blendv_epi8, _mm_cmpeq_epi8, _mm_cmpgt_epi8,
+    _mm_loadu_si128, _mm_madd_epi16, _mm_maddubs_epi16, _mm_or_si128, _mm_set_epi64x, _mm_set1_epi8, _mm_set1_epi16,
+    _mm_set1_epi32, _mm_shuffle_epi8, _mm_slli_si128, _mm_srli_epi16, _mm_srli_si128, _mm_storeu_si128,
+    _mm_testz_si128, _mm256_and_si256, _mm256_andnot_si256, _mm256_blend_epi32, _mm256_blendv_epi8,
+    _mm256_broadcastsi128_si256, _mm256_castsi256_si128, _mm256_cmpeq_epi8, _mm256_cmpgt_epi8,
+    _mm256_extracti128_si256, _mm256_madd_epi16, _mm256_maddubs_epi16, _mm256_or_si256, _mm256_set_epi64x,
+    _mm256_set_m128i, _mm256_set1_epi8, _mm256_set1_epi16, _mm256_set1_epi32, _mm256_shuffle_epi8, _mm256_slli_si256,
+    _mm256_srli_epi16, _mm256_storeu_si256, _mm256_testz_si256,
+};
+
+use super::{ALPHABET, DECODE, decode_entries, swar};
+
+/// Decodes 26 characters, returning `None` if they are no valid ULID.
+pub fn decode(bytes: &[u8; 26]) -> Option<u128> {
+    if has_avx2() {
+        // Safety: AVX2 is available
+        unsafe { decode_avx2(bytes) }
+    } else if has_sse41() {
+        // Safety: SSE4.1 is available
+        unsafe { decode_sse41(bytes) }
+    } else {
+        swar::decode(bytes)
+    }
+}
+
+/// Decodes consecutive entries of 26 characters into `values`, returning `false` if any entry is invalid.
+///
+/// The instruction set is detected once for all entries.
+pub fn decode_many(input: &[u8], values: &mut [u128]) -> bool {
+    if has_avx2() {
+        // Safety: AVX2 is available
+        unsafe { decode_many_avx2(input, values) }
+    } else if has_sse41() {
+        // Safety: SSE4.1 is available
+        unsafe { decode_many_sse41(input, values) }
+    } else {
+        swar::decode_many(input, values)
+    }
+}
+
+/// Encodes into 26 characters.
+pub fn encode(n: u128, buffer: &mut [u8; 26]) {
+    if has_avx2() {
+        // Safety: AVX2 is available
+        unsafe { encode_avx2(n, buffer) }
+    } else if has_sse41() {
+        // Safety: SSE4.1 is available
+        unsafe { encode_sse41(n, buffer) }
+    } else {
+        swar::encode(n, buffer);
+    }
+}
+
+/// Results of all implementations available on this CPU, for differential tests.
+#[cfg(test)]
+pub fn decode_each(bytes: &[u8; 26]) -> Vec<Option<u128>> {
+    let mut results = swar::decode_each(bytes);
+    if has_sse41() {
+        // Safety: SSE4.1 is available
+        results.push(unsafe { decode_sse41(bytes) });
+    }
+    if has_avx2() {
+        // Safety: AVX2 is available
+        results.push(unsafe { decode_avx2(bytes) });
+    }
+    results
+}
+
+/// Results of all implementations available on this CPU, for differential tests.
+#[cfg(test)]
+pub fn encode_each(n: u128) -> Vec<[u8; 26]> {
+    let mut results = swar::encode_each(n);
+    if has_sse41() {
+        let mut buffer = [0; 26];
+        // Safety: SSE4.1 is available
+        unsafe { encode_sse41(n, &mut buffer) };
+        results.push(buffer);
+    }
+    if has_avx2() {
+        let mut buffer = [0; 26];
+        // Safety: AVX2 is available
+        unsafe { encode_avx2(n, &mut buffer) };
+        results.push(buffer);
+    }
+    results
+}
+
+#[cfg(any(feature = "std", test))]
+fn has_sse41() -> bool {
+    std::arch::is_x86_feature_detected!("sse4.1")
+}
+
+#[cfg(not(any(feature = "std", test)))]
+const fn has_sse41() -> bool {
+    cfg!(target_feature = "sse4.1")
+}
+
+#[cfg(any(feature = "std", test))]
+fn has_avx2() -> bool {
+    std::arch::is_x86_feature_detected!("avx2")
+}
+
+#[cfg(not(any(feature = "std", test)))]
+const fn has_avx2() -> bool {
+    cfg!(target_feature = "avx2")
+}
+
+// Intrinsics without pointers are safe to call since Rust 1.87, but not in the supported Rust 1.85
+
+#[target_feature(enable = "sse4.1")]
+#[allow(unused_unsafe)]
+unsafe fn decode_sse41(bytes: &[u8; 26]) -> Option<u128> {
+    let mut words = [0_u32; 8];
+
+    // Safety: The loads and stores stay within the arrays, which need no alignment
+    unsafe {
+        let first = values_sse41(_mm_loadu_si128(bytes.as_ptr().cast()));
+        let last = values_sse41(_mm_loadu_si128(bytes[10..].as_ptr().cast()));
+
+        // Invalid characters have the high bit set
+        if _mm_testz_si128(_mm_or_si128(first, last), _mm_set1_epi8(i8::MIN)) == 0 {
+            return None;
+        }
+
+        // Put the first 10 values behind 6 padding zeros, followed by the last 16 values
+        let first = _mm_slli_si128::<6>(first);
+
+        _mm_storeu_si128(words.as_mut_ptr().cast(), pack_sse41(first));
+        _mm_storeu_si128(words[4..].as_mut_ptr().cast(), pack_sse41(last));
+    }
+
+    swar::join_words(words)
+}
+
+#[target_feature(enable = "sse4.1")]
+unsafe fn decode_many_sse41(input: &[u8], values: &mut [u128]) -> bool {
+    // Safety: SSE4.1 is available, as this function is only called then
+    decode_entries(input, values, |bytes| unsafe { decode_sse41(bytes) })
+}
+
+#[target_feature(enable = "sse4.1")]
+#[allow(unused_unsafe, clippy::cast_possible_wrap)]
+unsafe fn encode_sse41(n: u128, buffer: &mut [u8; 26]) {
+    let [g0, g1, g2, g3] = swar::encode_values(n);
+
+    // Safety: The stores stay within the buffer, which needs no alignment
+    unsafe {
+        let first = chars_sse41(_mm_set_epi64x(g1 as i64, g0 as i64));
+        let last = chars_sse41(_mm_set_epi64x(g3 as i64, g2 as i64));
+
+        // Drop the 6 padding characters, the last 16 characters overwrite the zeros shifted in
+        _mm_storeu_si128(buffer.as_mut_ptr().cast(), _mm_srli_si128::<6>(first));
+        _mm_storeu_si128(buffer[10..].as_mut_ptr().cast(), last);
+    }
+}
+
+/// Maps 16 characters to their values `0..=31`, and invalid characters to `0xFF`.
+#[target_feature(enable = "sse4.1")]
+#[allow(unused_unsafe)]
+unsafe fn values_sse41(chars: __m128i) -> __m128i {
+    // Safety: The loads stay within the decoding table, which needs no alignment
+    unsafe {
+        let low = _mm_and_si128(chars, _mm_set1_epi8(0x0F));
+        let high = _mm_and_si128(_mm_srli_epi16::<4>(chars), _mm_set1_epi8(0x0F));
+
+        // Setting bit 1 of the high nibble folds lowercase into uppercase letters
+        let folded = _mm_or_si128(high, _mm_set1_epi8(0x02));
+        let is_digit = _mm_cmpeq_epi8(high, _mm_set1_epi8(0x03));
+        let is_a_to_o = _mm_cmpeq_epi8(folded, _mm_set1_epi8(0x06));
+        let is_p_to_z = _mm_cmpeq_epi8(folded, _mm_set1_epi8(0x07));
+
+        let digits = _mm_shuffle_epi8(_mm_loadu_si128(DECODE[0x30..].as_ptr().cast()), low);
+        let a_to_o = _mm_shuffle_epi8(_mm_loadu_si128(DECODE[0x40..].as_ptr().cast()), low);
+        let p_to_z = _mm_shuffle_epi8(_mm_loadu_si128(DECODE[0x50..].as_ptr().cast()), low);
+
+        let valid_range = _mm_or_si128(_mm_or_si128(is_digit, is_a_to_o), is_p_to_z);
+        let invalid = _mm_andnot_si128(valid_range, _mm_set1_epi8(-1));
+
+        _mm_or_si128(
+            _mm_or_si128(_mm_and_si128(is_digit, digits), _mm_and_si128(is_a_to_o, a_to_o)),
+            _mm_or_si128(_mm_and_si128(is_p_to_z, p_to_z), invalid),
+        )
+    }
+}
+
+/// Maps 16 values `0..=31` to their characters.
+#[target_feature(enable = "sse4.1")]
+#[allow(unused_unsafe)]
+unsafe fn chars_sse41(values: __m128i) -> __m128i {
+    // Safety: The loads stay within the alphabet, which needs no alignment
+    unsafe {
+        let low = _mm_shuffle_epi8(_mm_loadu_si128(ALPHABET.as_ptr().cast()), values);
+        let high = _mm_shuffle_epi8(_mm_loadu_si128(ALPHABET[16..].as_ptr().cast()), values);
+        _mm_blendv_epi8(low, high, _mm_cmpgt_epi8(values, _mm_set1_epi8(15)))
+    }
+}
+
+/// Packs 16 values of 5 bits into four words of 20 bits, the first values into the most significant bits.
+#[target_feature(enable = "sse4.1")]
+#[allow(unused_unsafe)]
+unsafe fn pack_sse41(values: __m128i) -> __m128i {
+    // Safety: No memory is accessed
+    unsafe {
+        let pairs = _mm_maddubs_epi16(values, _mm_set1_epi16(0x0120));
+        _mm_madd_epi16(pairs, _mm_set1_epi32(0x0001_0400))
+    }
+}
+
+#[target_feature(enable = "avx2")]
+#[allow(unused_unsafe)]
+unsafe fn decode_avx2(bytes: &[u8; 26]) -> Option<u128> {
+    let mut words = [0_u32; 8];
+
+    // Safety: The loads and stores stay within the arrays, which need no alignment
+    unsafe {
+        let first = _mm_loadu_si128(bytes.as_ptr().cast());
+        let last = _mm_loadu_si128(bytes[10..].as_ptr().cast());
+        let values = values_avx2(_mm256_set_m128i(last, first));
+
+        // Invalid characters have the high bit set
+        if _mm256_testz_si256(values, _mm256_set1_epi8(i8::MIN)) == 0 {
+            return None;
+        }
+
+        // Put the first 10 values behind 6 padding zeros, followed by the last 16 values
+        let values = _mm256_blend_epi32::<0b1111_0000>(_mm256_slli_si256::<6>(values), values);
+
+        let pairs = _mm256_maddubs_epi16(values, _mm256_set1_epi16(0x0120));
+        let packed = _mm256_madd_epi16(pairs, _mm256_set1_epi32(0x0001_0400));
+        _mm256_storeu_si256(words.as_mut_ptr().cast(), packed);
+    }
+
+    swar::join_words(words)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn decode_many_avx2(input: &[u8], values: &mut [u128]) -> bool {
+    // Safety: AVX2 is available, as this function is only called then
+    decode_entries(input, values, |bytes| unsafe { decode_avx2(bytes) })
+}
+
+#[target_feature(enable = "avx2")]
+#[allow(unused_unsafe, clippy::cast_possible_wrap)]
+unsafe fn encode_avx2(n: u128, buffer: &mut [u8; 26]) {
+    let [g0, g1, g2, g3] = swar::encode_values(n);
+
+    // Safety: The stores stay within the buffer, which needs no alignment
+    unsafe {
+        let chars = chars_avx2(_mm256_set_epi64x(g3 as i64, g2 as i64, g1 as i64, g0 as i64));
+
+        // Drop the 6 padding characters, the last 16 characters overwrite the zeros shifted in
+        let first = _mm_srli_si128::<6>(_mm256_castsi256_si128(chars));
+        _mm_storeu_si128(buffer.as_mut_ptr().cast(), first);
+        _mm_storeu_si128(buffer[10..].as_mut_ptr().cast(), _mm256_extracti128_si256::<1>(chars));
+    }
+}
+
+/// Maps 32 characters to their values `0..=31`, and invalid characters to `0xFF`.
+#[target_feature(enable = "avx2")]
+#[allow(unused_unsafe)]
+unsafe fn values_avx2(chars: __m256i) -> __m256i {
+    // Safety: The loads stay within the decoding table, which needs no alignment
+    unsafe {
+        let low = _mm256_and_si256(chars, _mm256_set1_epi8(0x0F));
+        let high = _mm256_and_si256(_mm256_srli_epi16::<4>(chars), _mm256_set1_epi8(0x0F));
+
+        // Setting bit 1 of the high nibble folds lowercase into uppercase letters
+        let folded = _mm256_or_si256(high, _mm256_set1_epi8(0x02));
+        let is_digit = _mm256_cmpeq_epi8(high, _mm256_set1_epi8(0x03));
+        let is_a_to_o = _mm256_cmpeq_epi8(folded, _mm256_set1_epi8(0x06));
+        let is_p_to_z = _mm256_cmpeq_epi8(folded, _mm256_set1_epi8(0x07));
+
+        let digits = _mm256_broadcastsi128_si256(_mm_loadu_si128(DECODE[0x30..].as_ptr().cast()));
+        let a_to_o = _mm256_broadcastsi128_si256(_mm_loadu_si128(DECODE[0x40..].as_ptr().cast()));
+        let p_to_z = _mm256_broadcastsi128_si256(_mm_loadu_si128(DECODE[0x50..].as_ptr().cast()));
+        let digits = _mm256_shuffle_epi8(digits, low);
+        let a_to_o = _mm256_shuffle_epi8(a_to_o, low);
+        let p_to_z = _mm256_shuffle_epi8(p_to_z, low);
+
+        let valid_range = _mm256_or_si256(_mm256_or_si256(is_digit, is_a_to_o), is_p_to_z);
+        let invalid = _mm256_andnot_si256(valid_range, _mm256_set1_epi8(-1));
+
+        _mm256_or_si256(
+            _mm256_or_si256(_mm256_and_si256(is_digit, digits), _mm256_and_si256(is_a_to_o, a_to_o)),
+            _mm256_or_si256(_mm256_and_si256(is_p_to_z, p_to_z), invalid),
+        )
+    }
+}
+
+/// Maps 32 values `0..=31` to their characters.
+#[target_feature(enable = "avx2")]
+#[allow(unused_unsafe)]
+unsafe fn chars_avx2(values: __m256i) -> __m256i {
+    // Safety: The loads stay within the alphabet, which needs no alignment
+    unsafe {
+        let low = _mm256_broadcastsi128_si256(_mm_loadu_si128(ALPHABET.as_ptr().cast()));
+        let high = _mm256_broadcastsi128_si256(_mm_loadu_si128(ALPHABET[16..].as_ptr().cast()));
+        let low = _mm256_shuffle_epi8(low, values);
+        let high = _mm256_shuffle_epi8(high, values);
+        _mm256_blendv_epi8(low, high, _mm256_cmpgt_epi8(values, _mm256_set1_epi8(15)))
+    }
+}
@@ -0,0 +1,207 @@
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::str::from_utf8_unchecked;
+
+use crate::{BatchError, Error, Ulid, base32, util};
+
+/// Number of entries decoded at once by [`decode_many()`], before checking their validity.
+const BLOCK: usize = 64;
+
+/// Encodes ULIDs as fixed-stride text, 26 bytes per ULID without any separator.
+///
+/// Writes `26 * ulids.len()` bytes to the start of `buffer`, and returns them as a string.
+///
+/// # Panics
+///
+/// Panics if `buffer` is shorter than `26 * ulids.len()` bytes.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::Ulid;
+///
+/// let ulids = [Ulid::from_u128(1).unwrap(), Ulid::from_u128(2).unwrap()];
+/// let mut buffer = [0; 52];
+///
+// cspell:disable-next-line
+/// assert_eq!(mr_ulid::encode_many(&ulids, &mut buffer), "0000000000000000000000000100000000000000000000000002");
+/// ```
+pub fn encode_many<'a>(ulids: &[Ulid], buffer: &'a mut [u8]) -> &'a str {
+    let buffer = &mut buffer[..26 * ulids.len()];
+
+    for (ulid, chunk) in ulids.iter().zip(buffer.chunks_exact_mut(26)) {
+        base32::encode(ulid.to_u128(), chunk.try_into().unwrap());
+    }
+
+    // Safety: Every byte was written by `base32::encode()`, which guarantees valid UTF-8
+    unsafe { from_utf8_unchecked(buffer) }
+}
+
+/// Decodes fixed-stride text of 26 bytes per ULID, as written by [`encode_many()`].
+///
+/// Entries are decoded in blocks, checking the validity of a whole block at once. Only a block with an invalid
+/// entry is decoded again one entry at a time, to find the error. On error, the ULIDs before the invalid entry
+/// are already decoded, the others are left unchanged.
+///
+/// # Errors
+///
+/// Returns a [`BatchError`] with the index of the first invalid entry and the cause.
+///
+/// If `input` isn't exactly `26 * ulids.len()` bytes long, no ULID is decoded, and the error is reported
+/// for the first incomplete entry as [`Error::TooShort`], or for the last entry as [`Error::TooLong`].
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::{Error, Ulid};
+///
+/// let mut ulids = [Ulid::MIN; 2];
+///
+// cspell:disable-next-line
+/// mr_ulid::decode_many(b"0000000000000000000000000100000000000000000000000002", &mut ulids)?;
+///
+/// assert_eq!(ulids[1].to_u128(), 2);
+///
+// cspell:disable-next-line
+/// let error = mr_ulid::decode_many(b"000000000000000000000000010000000000000", &mut ulids).unwrap_err();
+/// assert_eq!(error.index(), 1);
+/// assert_eq!(error.error(), Error::TooShort { expected: 26, actual: 13 });
+/// # Ok::<(), mr_ulid::BatchError>(())
+/// ```
+pub fn decode_many(input: &[u8], ulids: &mut [Ulid]) -> Result<(), BatchError> {
+    check_length(input.len(), ulids.len())?;
+
+    let mut values = [0; BLOCK];
+
+    for (block, (input, ulids)) in input.chunks(26 * BLOCK).zip(ulids.chunks_mut(BLOCK)).enumerate() {
+        let start = block * BLOCK;
+        let values = &mut values[..ulids.len()];
+
+        if base32::decode_many(input, values) {
+            for (index, (ulid, &value)) in ulids.iter_mut().zip(values.iter()).enumerate() {
+                *ulid = Ulid::from_u128(value).ok_or_else(|| BatchError::new(start + index, Error::InvalidZero))?;
+            }
+        } else {
+            for (index, (chunk, ulid)) in input.chunks_exact(26).zip(ulids).enumerate() {
+                *ulid = decode_entry(chunk).map_err(|error| BatchError::new(start + index, error))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `len` bytes hold exactly `count` entries of 26 bytes.
+const fn check_length(len: usize, count: usize) -> Result<(), BatchError> {
+    let expected = 26 * count;
+
+    if len < expected {
+        let index = len / 26;
+        return Err(BatchError::new(
+            index,
+            Error::TooShort {
+                expected: 26,
+                actual: len - 26 * index,
+            },
+        ));
+    }
+
+    if len > expected {
+        let index = count.saturating_sub(1);
+        return Err(BatchError::new(
+            index,
+            Error::TooLong {
+                expected: expected - 26 * index,
+                actual: len - 26 * index,
+            },
+        ));
+    }
+
+    Ok(())
+}
+
+/// Encodes ULIDs as text, separated by `separator` (e.g. `b'\n'` or `b','`).
+///
+/// There is no separator after the last ULID.
+///
+/// # Panics
+///
+/// Panics if `separator` isn't an ASCII character.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::Ulid;
+///
+/// let ulids = [Ulid::from_u128(1).unwrap(), Ulid::from_u128(2).unwrap()];
+///
+/// assert_eq!(
+///     mr_ulid::encode_separated(&ulids, b','),
+///     "00000000000000000000000001,00000000000000000000000002"
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn encode_separated(ulids: &[Ulid], separator: u8) -> String {
+    assert!(separator.is_ascii(), "separator must be an ASCII character");
+
+    let mut s = String::with_capacity(27 * ulids.len());
+    let mut buffer = [0; 26];
+
+    for (index, ulid) in ulids.iter().enumerate() {
+        if index > 0 {
+            s.push(char::from(separator));
+        }
+        s.push_str(base32::encode(ulid.to_u128(), &mut buffer));
+    }
+
+    s
+}
+
+/// Decodes text of ULIDs separated by `separator` (e.g. `b'\n'` or `b','`).
+///
+/// A single trailing separator is ignored, and empty input yields no ULIDs.
+/// With `b'\n'` as separator, lines may also end with `"\r\n"`. Entries must not contain whitespace.
+///
+/// # Errors
+///
+/// Returns a [`BatchError`] with the index of the first invalid entry and the cause.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::Error;
+///
+/// let ulids = mr_ulid::decode_separated(b"00000000000000000000000001\n00000000000000000000000002\n", b'\n')?;
+/// assert_eq!(ulids.len(), 2);
+///
+/// let error = mr_ulid::decode_separated(b"00000000000000000000000001,0000000000000000000000000U", b',').unwrap_err();
+/// assert_eq!(error.index(), 1);
+/// assert_eq!(error.error(), Error::InvalidChar { offset: 25, byte: b'U' });
+/// # Ok::<(), mr_ulid::BatchError>(())
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_separated(input: &[u8], separator: u8) -> Result<Vec<Ulid>, BatchError> {
+    let input = input.strip_suffix(&[separator]).unwrap_or(input);
+
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut ulids = Vec::with_capacity(input.len() / 27 + 1);
+
+    for (index, entry) in input.split(|&byte| byte == separator).enumerate() {
+        let entry = if separator == b'\n' {
+            entry.strip_suffix(b"\r").unwrap_or(entry)
+        } else {
+            entry
+        };
+        ulids.push(decode_entry(entry).map_err(|error| BatchError::new(index, error))?);
+    }
+
+    Ok(ulids)
+}
+
+fn decode_entry(entry: &[u8]) -> Result<Ulid, Error> {
+    Ulid::from_ascii(util::as_array(entry)?)
+}
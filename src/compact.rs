@@ -0,0 +1,139 @@
+#[cfg(feature = "alloc")]
+use core::str::from_utf8_unchecked;
+
+use crate::{Error, util};
+
+/// Length of all compact encodings of 128 bits.
+pub const LEN: usize = 22;
+
+/// The digits of an encoding, and the values of the characters (`-1` for invalid characters).
+pub struct Alphabet<const N: usize> {
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    digits: [u8; N],
+    values: [i8; 256],
+}
+
+impl<const N: usize> Alphabet<N> {
+    const fn new(digits: [u8; N]) -> Self {
+        let mut values = [-1; 256];
+        let mut i = 0;
+        while i < N {
+            // `N` is at most 64
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            let value = i as i8;
+            values[digits[i] as usize] = value;
+            i += 1;
+        }
+        Self { digits, values }
+    }
+
+    fn value(&self, offset: usize, byte: u8) -> Result<u128, Error> {
+        u128::try_from(self.values[usize::from(byte)]).or(Err(Error::InvalidChar { offset, byte }))
+    }
+}
+
+/// Bitcoin's Base58 alphabet, without `0`, `O`, `I` and `l`. In ASCII order, so the encoding is sort-preserving.
+// cspell:disable-next-line
+pub static BASE58: Alphabet<58> = Alphabet::new(*b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz");
+
+/// Digits, uppercase and lowercase letters. In ASCII order, so the encoding is sort-preserving.
+// cspell:disable-next-line
+pub static BASE62: Alphabet<62> = Alphabet::new(*b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz");
+
+/// The URL-safe Base64 alphabet of RFC 4648.
+// cspell:disable-next-line
+static BASE64URL: Alphabet<64> = Alphabet::new(*b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_");
+
+/// Encodes `n` as a number of 22 digits, with leading zero digits.
+#[cfg(feature = "alloc")]
+pub fn encode<'a, const N: usize>(alphabet: &Alphabet<N>, mut n: u128, buffer: &'a mut [u8; LEN]) -> &'a str {
+    let radix = N as u128;
+
+    for byte in buffer.iter_mut().rev() {
+        // The remainder is less than `N`
+        #[allow(clippy::cast_possible_truncation)]
+        let digit = (n % radix) as usize;
+        *byte = alphabet.digits[digit];
+        n /= radix;
+    }
+
+    // Safety: Encoding above guarantees valid UTF-8
+    unsafe { from_utf8_unchecked(buffer) }
+}
+
+/// Decodes a number of exactly 22 digits, rejecting values larger than 128 bits.
+pub fn decode<const N: usize>(alphabet: &Alphabet<N>, bytes: &[u8]) -> Result<u128, Error> {
+    let bytes: &[u8; LEN] = util::as_array(bytes)?;
+    let mut n: u128 = 0;
+
+    for (offset, &byte) in bytes.iter().enumerate() {
+        let digit = alphabet.value(offset, byte)?;
+        n = n
+            .checked_mul(N as u128)
+            .and_then(|n| n.checked_add(digit))
+            .ok_or(Error::ValueTooLarge)?;
+    }
+
+    Ok(n)
+}
+
+/// Encodes the 16 bytes of `n` (big-endian) as unpadded base64url of RFC 4648.
+///
+/// The 22 characters hold 132 bits, so the last character carries 4 zero bits.
+#[cfg(feature = "alloc")]
+pub fn encode_base64url(n: u128, buffer: &mut [u8; LEN]) -> &str {
+    let [head @ .., last] = buffer;
+
+    *last = BASE64URL.digits[((n & 0x3) << 4) as usize];
+    let mut n = n >> 2;
+    for byte in head.iter_mut().rev() {
+        *byte = BASE64URL.digits[(n & 0x3F) as usize];
+        n >>= 6;
+    }
+
+    // Safety: Encoding above guarantees valid UTF-8
+    unsafe { from_utf8_unchecked(buffer) }
+}
+
+/// Decodes unpadded base64url of RFC 4648, rejecting non-zero bits after the 128 bits.
+pub fn decode_base64url(bytes: &[u8]) -> Result<u128, Error> {
+    let bytes: &[u8; LEN] = util::as_array(bytes)?;
+    let [head @ .., last] = bytes;
+    let mut n: u128 = 0;
+
+    for (offset, &byte) in head.iter().enumerate() {
+        n = (n << 6) | BASE64URL.value(offset, byte)?;
+    }
+
+    let last_value = BASE64URL.value(LEN - 1, *last)?;
+    if last_value & 0xF != 0 {
+        return Err(Error::InvalidChar {
+            offset: LEN - 1,
+            byte: *last,
+        });
+    }
+
+    Ok((n << 2) | (last_value >> 4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alphabets_sorted() {
+        assert!(BASE58.digits.is_sorted());
+        assert!(BASE62.digits.is_sorted());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_roundtrip() {
+        let mut buffer = [0; LEN];
+        for n in [0, 1, 57, 58, 61, 62, 1 << 64, u128::MAX - 1, u128::MAX] {
+            assert_eq!(decode(&BASE58, encode(&BASE58, n, &mut buffer).as_bytes()), Ok(n));
+            assert_eq!(decode(&BASE62, encode(&BASE62, n, &mut buffer).as_bytes()), Ok(n));
+            assert_eq!(decode_base64url(encode_base64url(n, &mut buffer).as_bytes()), Ok(n));
+        }
+    }
+}
@@ -1,34 +1,429 @@
-use std::fmt;
+use core::fmt;
 
 /// Errors that can occur when creating ULIDs out of foreign data.
+///
+/// Errors while parsing carry the details needed to point users to the problem:
+/// The expected and actual length of the input, or the offset and value of the offending byte.
+/// [`Error::with_input()`] renders a caret-style message pointing at the problem.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::{Error, Ulid};
+///
+/// let input = "01JB5C84ZBM8QVBE5QRZW6H*89";
+/// let error = input.parse::<Ulid>().unwrap_err();
+///
+/// assert_eq!(error, Error::InvalidChar { offset: 23, byte: b'*' });
+/// assert_eq!(error.to_string(), "invalid character '*' at offset 23");
+/// assert_eq!(
+///     error.with_input(input).to_string(),
+///     "invalid character '*' at offset 23\n01JB5C84ZBM8QVBE5QRZW6H*89\n                       ^"
+/// );
+/// ```
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Error {
     /// The input is too short.
-    TooShort,
+    TooShort {
+        /// The expected length of the input in bytes.
+        expected: usize,
+        /// The actual length of the input in bytes.
+        actual: usize,
+    },
     /// The input is too long.
-    TooLong,
+    TooLong {
+        /// The expected length of the input in bytes.
+        expected: usize,
+        /// The actual length of the input in bytes.
+        actual: usize,
+    },
     /// The ULID string contains an invalid character.
-    InvalidChar,
+    InvalidChar {
+        /// The byte offset of the invalid character in the input.
+        offset: usize,
+        /// The invalid byte (the first byte of a multi-byte UTF-8 character).
+        byte: u8,
+    },
+    /// The ULID string represents a value larger than 128 bits, as its first character is above `'7'`.
+    Overflow {
+        /// The byte offset of the first character of the ULID in the input.
+        offset: usize,
+        /// The first byte of the ULID string.
+        byte: u8,
+    },
+    /// The ULID string contains a valid character which is not in canonical form
+    /// (lowercase letters, or `I`, `L`, `O` instead of `1` and `0`), and only the canonical form is accepted.
+    NonCanonicalChar {
+        /// The byte offset of the non-canonical character in the input.
+        offset: usize,
+        /// The non-canonical byte.
+        byte: u8,
+    },
+    /// The Base58 or Base62 string represents a value larger than 128 bits.
+    ValueTooLarge,
     /// The value for the ULID is zero.
     InvalidZero,
     /// The given timestamp for the ULID is too large.
     TimestampOutOfRange,
     /// The given randomness for the ULID is too large.
     RandomnessOutOfRange,
+    /// The UUID is no UUID v7, as its version or variant bits are wrong.
+    NotUuidV7,
+    /// The check symbol of a human-readable ULID doesn't match the ULID.
+    ChecksumMismatch {
+        /// The likely typo, if exactly one correction between easily confused characters fixes the check symbol.
+        typo: Option<Typo>,
+    },
 }
 
-impl std::error::Error for Error {}
+impl Error {
+    /// Returns the byte offset in the input where the error was detected, if the error relates to a position.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// assert_eq!("0000000000000000000000000U".parse::<Ulid>().unwrap_err().offset(), Some(25));
+    /// assert_eq!("80000000000000000000000000".parse::<Ulid>().unwrap_err().offset(), Some(0));
+    /// assert_eq!("0000".parse::<Ulid>().unwrap_err().offset(), Some(4));
+    /// ```
+    #[must_use]
+    pub const fn offset(&self) -> Option<usize> {
+        match *self {
+            Self::TooShort { actual, .. } => Some(actual),
+            Self::TooLong { expected, .. } => Some(expected),
+            Self::InvalidChar { offset, .. }
+            | Self::Overflow { offset, .. }
+            | Self::NonCanonicalChar { offset, .. } => Some(offset),
+            Self::ChecksumMismatch { typo: Some(typo) } => Some(typo.offset),
+            Self::ValueTooLarge
+            | Self::InvalidZero
+            | Self::TimestampOutOfRange
+            | Self::RandomnessOutOfRange
+            | Self::NotUuidV7
+            | Self::ChecksumMismatch { typo: None } => None,
+        }
+    }
+
+    /// Returns the error with its offset moved by `by` bytes, for a ULID embedded in a larger input.
+    ///
+    /// The lengths of [`Error::TooShort`] and [`Error::TooLong`] become lengths of the whole input,
+    /// so [`Error::offset()`] points into the whole input as well.
+    pub(crate) const fn shifted(self, by: usize) -> Self {
+        match self {
+            Self::TooShort { expected, actual } => Self::TooShort {
+                expected: expected + by,
+                actual: actual + by,
+            },
+            Self::TooLong { expected, actual } => Self::TooLong {
+                expected: expected + by,
+                actual: actual + by,
+            },
+            Self::InvalidChar { offset, byte } => Self::InvalidChar {
+                offset: offset + by,
+                byte,
+            },
+            Self::Overflow { offset, byte } => Self::Overflow {
+                offset: offset + by,
+                byte,
+            },
+            Self::NonCanonicalChar { offset, byte } => Self::NonCanonicalChar {
+                offset: offset + by,
+                byte,
+            },
+            other => other,
+        }
+    }
+
+    /// Returns a caret-style rendering of the error for the given input.
+    ///
+    /// The rendered message consists of three lines: The error message, the input,
+    /// and a caret (`^`) below the character at [`Error::offset()`].
+    /// Errors without an offset are rendered as the error message only.
+    /// Control characters in the input are rendered as `?`.
+    #[must_use]
+    pub const fn with_input(self, input: &str) -> ErrorWithInput<'_> {
+        ErrorWithInput { error: self, input }
+    }
+}
+
+impl core::error::Error for Error {}
 
 impl fmt::Display for Error {
+    /// Formats the error message for display.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::TooShort { expected, actual } => {
+                write!(f, "input is too short: expected {expected} bytes, found {actual}")
+            }
+            Self::TooLong { expected, actual } => {
+                write!(f, "input is too long: expected {expected} bytes, found {actual}")
+            }
+            Self::InvalidChar { offset, byte } if byte.is_ascii_graphic() || byte == b' ' => {
+                write!(f, "invalid character '{}' at offset {offset}", char::from(byte))
+            }
+            Self::InvalidChar { offset, byte } => write!(f, "invalid byte 0x{byte:02X} at offset {offset}"),
+            Self::Overflow { byte, .. } => write!(
+                f,
+                "value is too large: first character '{}' is above '7'",
+                char::from(byte)
+            ),
+            Self::NonCanonicalChar { offset, byte } => {
+                write!(f, "non-canonical character '{}' at offset {offset}", char::from(byte))
+            }
+            Self::ValueTooLarge => write!(f, "value is too large for 128 bits"),
+            Self::InvalidZero => write!(f, "invalid zero value"),
+            Self::TimestampOutOfRange => write!(f, "timestamp is too large"),
+            Self::RandomnessOutOfRange => write!(f, "randomness is too large"),
+            Self::NotUuidV7 => write!(f, "not a UUID v7: wrong version or variant"),
+            Self::ChecksumMismatch { typo: Some(typo) } => write!(f, "checksum mismatch: likely {typo}"),
+            Self::ChecksumMismatch { typo: None } => write!(f, "checksum mismatch"),
+        }
+    }
+}
+
+/// A caret-style rendering of an [`Error`] for a given input, created by [`Error::with_input()`].
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorWithInput<'a> {
+    error: Error,
+    input: &'a str,
+}
+
+impl fmt::Display for ErrorWithInput<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+
+        let Some(offset) = self.error.offset() else {
+            return Ok(());
+        };
+
+        f.write_str("\n")?;
+        for c in self.input.chars() {
+            write!(f, "{}", if c.is_control() { '?' } else { c })?;
+        }
+
+        // Count characters instead of bytes, so the caret lines up with multi-byte characters
+        let column = self.input.get(..offset).map_or(offset, |prefix| prefix.chars().count());
+
+        write!(f, "\n{:column$}^", "")
+    }
+}
+
+/// A likely typo in a human-readable ULID: A character which is easily confused with the correct one.
+///
+/// Returned in [`Error::ChecksumMismatch`] and by [`typo_candidates()`](crate::typo_candidates).
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Typo {
+    offset: usize,
+    found: u8,
+    expected: u8,
+}
+
+impl Typo {
+    pub(crate) const fn new(offset: usize, found: u8, expected: u8) -> Self {
+        Self {
+            offset,
+            found,
+            expected,
+        }
+    }
+
+    /// Returns the byte offset of the mistyped character in the input.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the mistyped byte, as found in the input.
+    #[must_use]
+    pub const fn found(&self) -> u8 {
+        self.found
+    }
+
+    /// Returns the byte which makes the check symbol match, in canonical form.
+    #[must_use]
+    pub const fn expected(&self) -> u8 {
+        self.expected
+    }
+}
+
+impl fmt::Display for Typo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "typo '{}' at offset {}, expected '{}'",
+            char::from(self.found),
+            self.offset,
+            char::from(self.expected)
+        )
+    }
+}
+
+/// An error in a batch of ULIDs, with the index of the first invalid entry.
+///
+/// Returned by [`decode_many()`](crate::decode_many) and `decode_separated()`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct BatchError {
+    index: usize,
+    error: Error,
+}
+
+impl BatchError {
+    pub(crate) const fn new(index: usize, error: Error) -> Self {
+        Self { index, error }
+    }
+
+    /// Returns the index of the first invalid entry.
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns why the entry is invalid. Offsets are relative to the start of the entry.
+    #[must_use]
+    pub const fn error(&self) -> Error {
+        self.error
+    }
+}
+
+impl core::error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid entry {}: {}", self.index, self.error)
+    }
+}
+
+/// Errors that can occur when parsing a [`PrefixedUlid`](crate::PrefixedUlid).
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PrefixedUlidError {
+    /// The input doesn't start with the expected prefix, followed by an underscore.
+    WrongPrefix {
+        /// The expected prefix, without the separating underscore.
+        expected: &'static str,
+    },
+    /// The ULID after the prefix is malformed. Offsets and lengths are relative to the whole input.
+    InvalidUlid(Error),
+}
+
+impl core::error::Error for PrefixedUlidError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidUlid(error) => Some(error),
+            Self::WrongPrefix { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for PrefixedUlidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongPrefix { expected } => write!(f, "wrong prefix: expected '{expected}_'"),
+            Self::InvalidUlid(error) => write!(f, "invalid ULID: {error}"),
+        }
+    }
+}
+
+/// Errors that can occur when parsing or creating a [`TypeId`](crate::TypeId).
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TypeIdError {
+    /// The prefix is not 1 to 63 lowercase ASCII letters and underscores, starting and ending with a letter.
+    InvalidPrefix,
+    /// The suffix is malformed. Offsets and lengths are relative to the whole input.
+    InvalidSuffix(Error),
+}
+
+impl core::error::Error for TypeIdError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidSuffix(error) => Some(error),
+            Self::InvalidPrefix => None,
+        }
+    }
+}
+
+impl fmt::Display for TypeIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrefix => f.write_str("invalid TypeID prefix"),
+            Self::InvalidSuffix(error) => write!(f, "invalid TypeID suffix: {error}"),
+        }
+    }
+}
+
+/// Errors that can occur when generating ULIDs.
+///
+/// Returned by [`Ulid::try_generate()`](crate::Ulid::try_generate),
+/// [`ZeroableUlid::try_generate()`](crate::ZeroableUlid::try_generate), [`UlidLease`](crate::UlidLease)
+/// and `KeyedGenerator::try_generate()`.
+#[cfg(any(feature = "std", feature = "critical-section"))]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GenerateError {
+    /// No entropy source is set (see [`NO_ENTROPY_SOURCE`](crate::NO_ENTROPY_SOURCE)).
+    NoEntropySource,
+    /// The system clock is set before the Unix epoch.
+    ClockBeforeEpoch,
+    /// The entropy source returned no timestamp.
+    TimestampUnavailable,
+    /// The timestamp of the entropy source is too large.
+    TimestampOutOfRange,
+    /// The entropy source returned no random value.
+    RandomUnavailable,
+    /// The entropy source returned a random value outside the requested range.
+    RandomnessOutOfRange,
+    /// The continuous health tests on random values failed (see [`set_health_tests()`](crate::set_health_tests)).
+    HealthTestFailure(crate::HealthTestFailure),
+    /// The timestamp would run too far ahead of the clock (see [`set_max_ahead_of_clock()`](crate::set_max_ahead_of_clock)).
+    AheadOfClock,
+    /// All ULIDs up to the largest possible ULID have been generated.
+    Exhausted,
+    /// The lock of the generator is poisoned, as a thread panicked while holding it.
+    Poisoned,
+    /// A lease of zero ULIDs was requested (see [`UlidLease::acquire()`](crate::UlidLease::acquire)).
+    EmptyLease,
+    /// The shared state file could not be locked, read, or written, or contains no valid ULID
+    /// (see `set_shared_state_file()`).
+    #[cfg(feature = "shared-state")]
+    SharedState(std::io::ErrorKind),
+}
+
+#[cfg(any(feature = "std", feature = "critical-section"))]
+impl core::error::Error for GenerateError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::HealthTestFailure(failure) => Some(failure),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "critical-section"))]
+impl fmt::Display for GenerateError {
     /// Formats the error message for display.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let message = match *self {
-            Self::TooShort => "input is too short",
-            Self::TooLong => "input is too long",
-            Self::InvalidChar => "string contains an invalid character",
-            Self::InvalidZero => "invalid zero value",
+            Self::NoEntropySource => "no entropy source set",
+            Self::ClockBeforeEpoch => "system clock is set before the Unix epoch",
+            Self::TimestampUnavailable => "entropy source returned no timestamp",
             Self::TimestampOutOfRange => "timestamp is too large",
-            Self::RandomnessOutOfRange => "randomness is too large",
+            Self::RandomUnavailable => "entropy source returned no random value",
+            Self::RandomnessOutOfRange => "entropy source returned randomness out of range",
+            Self::HealthTestFailure(_) => "entropy source failed health tests",
+            Self::AheadOfClock => "timestamp would run too far ahead of the clock",
+            Self::Exhausted => "all possible ULIDs have been generated",
+            Self::Poisoned => "generator lock is poisoned",
+            Self::EmptyLease => "lease of zero ULIDs requested",
+            #[cfg(feature = "shared-state")]
+            Self::SharedState(kind) => return write!(f, "shared state file failed: {kind}"),
         };
         write!(f, "{message}")
     }
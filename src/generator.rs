@@ -1,6 +1,13 @@
-#[cfg(feature = "rand")]
+use alloc::{boxed::Box, vec::Vec};
+#[cfg(not(feature = "std"))]
+use core::cell::RefCell;
+use core::{fmt, num::NonZero, ops::RangeInclusive, time::Duration};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+#[cfg(any(feature = "rand", feature = "getrandom"))]
 use std::time::SystemTime;
-use std::{fmt, ops::RangeInclusive, sync::Mutex};
+#[cfg(feature = "shared-state")]
+use std::{io, path::Path};
 
 #[cfg(feature = "rand")]
 use rand::{
@@ -9,7 +16,14 @@ use rand::{
     rngs::{StdRng, SysRng}, // cspell:disable-line
 };
 
-use crate::{RANDOM_BITS, RANDOM_GEN_MAX, TIMESTAMP_MASK, TIMESTAMP_MAX};
+#[cfg(feature = "shared-state")]
+use crate::shared::SharedState;
+use crate::{
+    NANOS_PER_MILLI, RANDOM_BITS, RANDOM_GEN_MAX, RANDOM_MASK, SUB_MILLIS_BITS, SUB_MILLIS_RANDOM_BITS,
+    SUB_MILLIS_RANDOM_GEN_MAX, SUB_MILLIS_TICK_MASK, TIMESTAMP_MASK, TIMESTAMP_MAX,
+    error::GenerateError,
+    health::{HealthTestFailure, HealthTests},
+};
 
 /// Trait for entropy sources.
 ///
@@ -47,6 +61,14 @@ pub trait EntropySource: Send {
 
     /// Returns a random number in the given range.
     fn random(&mut self, range: RangeInclusive<u128>) -> Option<u128>;
+
+    /// Returns the current timestamp in nanoseconds since the Unix epoch.
+    ///
+    /// Only used if sub-millisecond precision is enabled (see [`set_sub_millisecond_precision()`]).
+    /// The default implementation has millisecond precision only, based on [`EntropySource::timestamp()`].
+    fn timestamp_nanos(&mut self) -> Option<u128> {
+        self.timestamp().map(|millis| u128::from(millis) * 1_000_000)
+    }
 }
 
 /// An opaque handle for entropy sources.
@@ -55,10 +77,15 @@ pub trait EntropySource: Send {
 ///
 /// - [`NO_ENTROPY_SOURCE`]
 /// - [`STANDARD_ENTROPY_SOURCE`]
+/// - [`OS_ENTROPY_SOURCE`]
 /// - Types implementing the [`EntropySource`] trait.
+/// - Fallback chains of other handles (see [`EntropySourceHandle::fallback()`]).
 ///
 /// A `EntropySourceHandle` is accepted by [`set_entropy_source`] function.
 ///
+/// A `EntropySourceHandle` implements the [`EntropySource`] trait itself, so entropy sources
+/// can wrap other entropy sources given as handles.
+///
 #[repr(transparent)]
 pub struct EntropySourceHandle {
     inner: InnerHandle,
@@ -66,9 +93,43 @@ pub struct EntropySourceHandle {
 
 enum InnerHandle {
     NoOp,
-    #[cfg(feature = "rand")]
-    Standard,
+    #[cfg(any(feature = "rand", feature = "getrandom"))]
+    Standard(StandardSource),
+    #[cfg(feature = "getrandom")]
+    Os,
     Custom(Box<dyn EntropySource>),
+    Fallback(FallbackChain),
+}
+
+#[cfg(any(feature = "rand", feature = "getrandom"))]
+struct StandardSource {
+    #[cfg(feature = "rand")]
+    rng: Option<Box<StdRng>>,
+}
+
+struct FallbackChain {
+    sources: Box<[EntropySourceHandle]>,
+    used: Option<usize>,
+}
+
+impl FallbackChain {
+    /// Returns the result of the first entropy source in the chain succeeding with `f`,
+    /// or the error of the last entropy source if all fail.
+    ///
+    /// `f` gets the index of the entropy source in the chain, and the entropy source.
+    fn first<T>(
+        &mut self,
+        mut f: impl FnMut(usize, &mut EntropySourceHandle) -> Result<T, GenerateError>,
+    ) -> Result<(usize, T), GenerateError> {
+        let mut error = GenerateError::NoEntropySource;
+        for (index, source) in self.sources.iter_mut().enumerate() {
+            match f(index, source) {
+                Ok(value) => return Ok((index, value)),
+                Err(e) => error = e,
+            }
+        }
+        Err(error)
+    }
 }
 
 impl EntropySourceHandle {
@@ -79,6 +140,154 @@ impl EntropySourceHandle {
             inner: InnerHandle::Custom(Box::new(source)),
         }
     }
+
+    /// Creates an `EntropySourceHandle` from an ordered list of entropy sources, falling back
+    /// to the next entropy source whenever one fails.
+    ///
+    /// Every timestamp and every random value is requested from the first entropy source, and only if it
+    /// returns `None`, from the second one, and so on. A random value outside the requested range, or
+    /// failing the health tests (see [`set_health_tests()`]), counts as a failure, too. A failing entropy source is asked again for the
+    /// next value, so the chain returns to a preferred source as soon as it recovers.
+    /// Generation only fails if all entropy sources fail.
+    ///
+    /// Which entropy source delivered the random values for the last generated ULID can be queried
+    /// with [`used_entropy_source()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "getrandom")]
+    /// # {
+    /// use mr_ulid::{EntropySourceHandle, OS_ENTROPY_SOURCE, STANDARD_ENTROPY_SOURCE, Ulid};
+    ///
+    /// # struct HardwareRng;
+    /// # impl mr_ulid::EntropySource for HardwareRng {
+    /// #     fn timestamp(&mut self) -> Option<u64> { None }
+    /// #     fn random(&mut self, _: std::ops::RangeInclusive<u128>) -> Option<u128> { None }
+    /// # }
+    /// let hardware = EntropySourceHandle::new(HardwareRng);
+    /// let chain = EntropySourceHandle::fallback([hardware, OS_ENTROPY_SOURCE, STANDARD_ENTROPY_SOURCE]);
+    /// mr_ulid::set_entropy_source(chain);
+    ///
+    /// assert!(Ulid::try_new().is_some());
+    /// assert_eq!(mr_ulid::used_entropy_source(), Some(1));
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn fallback(sources: impl IntoIterator<Item = Self>) -> Self {
+        Self {
+            inner: InnerHandle::Fallback(FallbackChain {
+                sources: sources.into_iter().collect(),
+                used: None,
+            }),
+        }
+    }
+
+    /// Returns the index of the entropy source of a fallback chain which delivered the last random value.
+    const fn used(&self) -> Option<usize> {
+        match &self.inner {
+            InnerHandle::Fallback(chain) => chain.used,
+            _ => None,
+        }
+    }
+
+    fn try_timestamp(&mut self) -> Result<u64, GenerateError> {
+        match &mut self.inner {
+            InnerHandle::NoOp => Err(GenerateError::NoEntropySource),
+            #[cfg(any(feature = "rand", feature = "getrandom"))]
+            InnerHandle::Standard(_) => since_epoch_millis(),
+            #[cfg(feature = "getrandom")]
+            InnerHandle::Os => since_epoch_millis(),
+            InnerHandle::Custom(source) => source.timestamp().ok_or(GenerateError::TimestampUnavailable),
+            InnerHandle::Fallback(chain) => chain
+                .first(|_, source| source.try_timestamp())
+                .map(|(_, millis)| millis),
+        }
+    }
+
+    fn try_random(&mut self, range: RangeInclusive<u128>) -> Result<u128, GenerateError> {
+        self.try_random_checked(range.clone(), &mut |_, candidate| {
+            if range.contains(&candidate) {
+                Ok(())
+            } else {
+                Err(GenerateError::RandomnessOutOfRange)
+            }
+        })
+    }
+
+    /// Returns a random value in `range`, which passed `check`.
+    ///
+    /// `check` gets the index of the entropy source in a fallback chain (0 otherwise), and the random value.
+    /// A fallback chain tries the next entropy source if the check fails.
+    fn try_random_checked(
+        &mut self,
+        range: RangeInclusive<u128>,
+        check: &mut dyn FnMut(usize, u128) -> Result<(), GenerateError>,
+    ) -> Result<u128, GenerateError> {
+        if let InnerHandle::Fallback(chain) = &mut self.inner {
+            let result = chain.first(|index, source| {
+                source.try_random_checked(range.clone(), &mut |_, candidate| check(index, candidate))
+            });
+            chain.used = result.ok().map(|(index, _)| index);
+            return result.map(|(_, value)| value);
+        }
+
+        let candidate = self.try_random_unchecked(range)?;
+        check(0, candidate)?;
+        Ok(candidate)
+    }
+
+    fn try_random_unchecked(&mut self, range: RangeInclusive<u128>) -> Result<u128, GenerateError> {
+        match &mut self.inner {
+            InnerHandle::NoOp => Err(GenerateError::NoEntropySource),
+            #[cfg(feature = "rand")]
+            InnerHandle::Standard(standard) => {
+                let rng = match &mut standard.rng {
+                    Some(rng) => rng,
+                    // Fails if the OS random number generator is unavailable; seeding is retried on the next call
+                    rng @ None => rng.insert(Box::new(
+                        StdRng::try_from_rng(&mut SysRng).map_err(|_| GenerateError::RandomUnavailable)?, // cspell::disable-line
+                    )),
+                };
+
+                Ok(rng.random_range(range))
+            }
+            #[cfg(all(feature = "getrandom", not(feature = "rand")))]
+            InnerHandle::Standard(_) => os_random(range).ok_or(GenerateError::RandomUnavailable),
+            #[cfg(feature = "getrandom")]
+            InnerHandle::Os => os_random(range).ok_or(GenerateError::RandomUnavailable),
+            InnerHandle::Custom(source) => source.random(range).ok_or(GenerateError::RandomUnavailable),
+            InnerHandle::Fallback(_) => unreachable!("fallback chains are handled by `try_random_checked()`"),
+        }
+    }
+
+    fn try_timestamp_nanos(&mut self) -> Result<u128, GenerateError> {
+        match &mut self.inner {
+            InnerHandle::NoOp => Err(GenerateError::NoEntropySource),
+            #[cfg(any(feature = "rand", feature = "getrandom"))]
+            InnerHandle::Standard(_) => Ok(since_epoch()?.as_nanos()),
+            #[cfg(feature = "getrandom")]
+            InnerHandle::Os => Ok(since_epoch()?.as_nanos()),
+            InnerHandle::Custom(source) => source.timestamp_nanos().ok_or(GenerateError::TimestampUnavailable),
+            InnerHandle::Fallback(chain) => chain
+                .first(|_, source| source.try_timestamp_nanos())
+                .map(|(_, nanos)| nanos),
+        }
+    }
+}
+
+impl EntropySource for EntropySourceHandle {
+    fn timestamp(&mut self) -> Option<u64> {
+        self.try_timestamp().ok()
+    }
+
+    fn random(&mut self, range: RangeInclusive<u128>) -> Option<u128> {
+        self.try_random(range).ok()
+    }
+
+    fn timestamp_nanos(&mut self) -> Option<u128> {
+        self.try_timestamp_nanos().ok()
+    }
 }
 
 impl fmt::Debug for EntropySourceHandle {
@@ -92,8 +301,10 @@ impl fmt::Debug for EntropySourceHandle {
 /// This is the default entropy source used to generate ULIDs if no
 /// other entropy source is set.
 ///
-/// This entropy source uses the system clock and the `rand` crate. So if
-/// the `rand` crate is disabled, this entropy source is not available.
+/// This entropy source uses the system clock and the `rand` crate. If the `rand` crate
+/// is disabled but the `getrandom` feature is enabled, random values are drawn directly
+/// from the operating system's random number generator instead.
+/// If both are disabled, this entropy source is not available.
 ///
 /// # Example
 ///
@@ -104,11 +315,34 @@ impl fmt::Debug for EntropySourceHandle {
 ///
 /// assert!(Ulid::try_new().is_some());
 /// ```
-#[cfg(feature = "rand")]
+#[cfg(any(feature = "rand", feature = "getrandom"))]
 pub const STANDARD_ENTROPY_SOURCE: EntropySourceHandle = EntropySourceHandle {
-    inner: InnerHandle::Standard,
+    inner: InnerHandle::Standard(StandardSource {
+        #[cfg(feature = "rand")]
+        rng: None,
+    }),
 };
 
+/// Operating system entropy source.
+///
+/// This entropy source uses the system clock and draws random values directly from the operating system's
+/// random number generator via the `getrandom` crate, without a user-space random number generator.
+///
+/// It is available with the `getrandom` feature, and is mainly useful within a fallback chain
+/// (see [`EntropySourceHandle::fallback()`]).
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::Ulid;
+///
+/// mr_ulid::set_entropy_source(mr_ulid::OS_ENTROPY_SOURCE);
+///
+/// assert!(Ulid::try_new().is_some());
+/// ```
+#[cfg(feature = "getrandom")]
+pub const OS_ENTROPY_SOURCE: EntropySourceHandle = EntropySourceHandle { inner: InnerHandle::Os };
+
 /// No-Operation entropy source.
 ///
 /// An entropy source which never generates any timestamps nor any random values.
@@ -117,7 +351,7 @@ pub const STANDARD_ENTROPY_SOURCE: EntropySourceHandle = EntropySourceHandle {
 /// - `Ulid::try_new()` will always return `None`.
 /// - `Ulid::new()` will panic.
 ///
-/// This entropy source is the default source if the `rand` crate is not enabled.
+/// This entropy source is the default source if neither the `rand` nor the `getrandom` feature is enabled.
 ///
 /// # Example
 ///
@@ -132,121 +366,459 @@ pub const NO_ENTROPY_SOURCE: EntropySourceHandle = EntropySourceHandle {
     inner: InnerHandle::NoOp,
 };
 
-struct Generator {
+pub(crate) struct Generator {
     source: EntropySourceHandle,
-    #[cfg(feature = "rand")]
-    rng: Option<StdRng>,
-    last_ulid: u128,
+    /// Health tests per entropy source of a fallback chain, or of the only entropy source.
+    health: Option<Vec<HealthTests>>,
+    max_ahead: Option<u64>,
+    sub_millis: bool,
+    #[cfg(feature = "shared-state")]
+    shared: Option<SharedState>,
+    pub(crate) last_ulid: u128,
 }
 
 impl Generator {
-    #[must_use]
-    fn generate(&mut self) -> Option<u128> {
-        let now = self.timestamp()?;
+    pub(crate) const fn new(source: EntropySourceHandle) -> Self {
+        Self {
+            source,
+            health: None,
+            max_ahead: None,
+            sub_millis: false,
+            #[cfg(feature = "shared-state")]
+            shared: None,
+            last_ulid: 0,
+        }
+    }
+
+    fn generate(&mut self) -> Result<u128, GenerateError> {
+        self.generate_range(NonZero::<u64>::MIN).map(|(start, _)| start)
+    }
+
+    /// Generates a range of up to `count` consecutive ULIDs, all greater than previously generated ULIDs.
+    ///
+    /// The range ends with the millisecond of its first ULID, so it may contain less than `count` ULIDs.
+    fn generate_range(&mut self, count: NonZero<u64>) -> Result<(u128, u128), GenerateError> {
+        #[cfg(feature = "shared-state")]
+        if let Some(mut shared) = self.shared.take() {
+            let mut range = Err(GenerateError::Exhausted);
+            let result = shared.update(|last_ulid| {
+                self.last_ulid = self.last_ulid.max(last_ulid);
+                range = self.next_range(count);
+                range.ok().map(|(_, end)| end)
+            });
+            self.shared = Some(shared);
+            result.map_err(|e| GenerateError::SharedState(e.kind()))?;
+            return range;
+        }
+
+        self.next_range(count)
+    }
+
+    /// Generates a range of ULIDs like [`Generator::generate_range()`], without considering a shared state file.
+    fn next_range(&mut self, count: NonZero<u64>) -> Result<(u128, u128), GenerateError> {
+        let start = self.next_ulid()?;
+
+        let millisecond_end = (start & TIMESTAMP_MASK) | RANDOM_MASK;
+        let end = start.saturating_add(u128::from(count.get() - 1)).min(millisecond_end);
+
+        self.last_ulid = end;
+
+        Ok((start, end))
+    }
+
+    /// Generates the next ULID after `last_ulid`, without considering a shared state file.
+    pub(crate) fn next_ulid(&mut self) -> Result<u128, GenerateError> {
+        let (now, fraction) = self.timestamp()?;
         assert!(now < TIMESTAMP_MAX); // Yes, smaller, *not* smaller or equal!
 
-        let timestamp = u128::from(now) << RANDOM_BITS;
-        let last_timestamp = self.last_ulid & TIMESTAMP_MASK;
+        // With sub-millisecond precision, the fraction is part of the "timestamp"
+        let (tick_mask, random_max) = if self.sub_millis {
+            (SUB_MILLIS_TICK_MASK, SUB_MILLIS_RANDOM_GEN_MAX)
+        } else {
+            (TIMESTAMP_MASK, RANDOM_GEN_MAX)
+        };
+
+        let tick = (u128::from(now) << RANDOM_BITS) | (fraction << SUB_MILLIS_RANDOM_BITS);
+        let last_tick = self.last_ulid & tick_mask;
 
-        let ulid = if timestamp > last_timestamp {
+        let ulid = if tick > last_tick {
             // Ensure ULID is always non-zero, regardless of timestamp
-            let random = self.random(1..=RANDOM_GEN_MAX)?;
-            timestamp | random
+            let random = self.random(1..=random_max)?;
+            tick | random
         } else {
-            self.last_ulid.checked_add(1)?
+            self.last_ulid.checked_add(1).ok_or(GenerateError::Exhausted)?
         };
 
         assert!(ulid > self.last_ulid);
 
+        if let Some(max_ahead) = self.max_ahead {
+            let ahead = ((ulid >> RANDOM_BITS) as u64).saturating_sub(now);
+            if ahead > max_ahead {
+                return Err(GenerateError::AheadOfClock);
+            }
+        }
+
         self.last_ulid = ulid;
 
-        Some(ulid)
+        Ok(ulid)
     }
 
-    #[must_use]
-    fn timestamp(&mut self) -> Option<u64> {
-        let candidate = match &mut self.source.inner {
-            InnerHandle::NoOp => None,
-            #[cfg(feature = "rand")]
-            InnerHandle::Standard => {
-                let now = SystemTime::now();
-                let since_epoch = now.duration_since(SystemTime::UNIX_EPOCH).ok()?;
-                let millis = since_epoch.as_millis();
-                u64::try_from(millis).ok()
-            }
-            InnerHandle::Custom(source) => source.timestamp(),
-        }?;
+    /// Returns the timestamp in milliseconds, and the fraction of the millisecond in units of 1/4096 ms.
+    ///
+    /// The fraction is always zero, unless sub-millisecond precision is enabled.
+    pub(crate) fn timestamp(&mut self) -> Result<(u64, u128), GenerateError> {
+        let (candidate, fraction) = if self.sub_millis {
+            let nanos = self.source.try_timestamp_nanos()?;
+            let millis = u64::try_from(nanos / NANOS_PER_MILLI).map_err(|_| GenerateError::TimestampOutOfRange)?;
+            let fraction = ((nanos % NANOS_PER_MILLI) << SUB_MILLIS_BITS) / NANOS_PER_MILLI;
+            (millis, fraction)
+        } else {
+            (self.source.try_timestamp()?, 0)
+        };
 
         // The last possible millisecond (TIMESTAMP_MAX) is reserved for our guarantees.
-        (candidate < TIMESTAMP_MAX).then_some(candidate)
+        if candidate < TIMESTAMP_MAX {
+            Ok((candidate, fraction))
+        } else {
+            Err(GenerateError::TimestampOutOfRange)
+        }
     }
 
-    #[must_use]
-    fn random(&mut self, range: RangeInclusive<u128>) -> Option<u128> {
-        let candidate = match &mut self.source.inner {
-            InnerHandle::NoOp => None,
-            #[cfg(feature = "rand")]
-            InnerHandle::Standard => {
-                let rng = self
-                    .rng
-                    .get_or_insert_with(|| StdRng::try_from_rng(&mut SysRng).unwrap()); // cspell::disable-line
+    fn random(&mut self, range: RangeInclusive<u128>) -> Result<u128, GenerateError> {
+        let health = &mut self.health;
 
-                // TODO: Once Rust 2027 arrives, `RangeInclusive` should be `Copy`, so remove `clone()` then.
-                Some(rng.random_range(range.clone()))
+        // Checked per entropy source, so a fallback chain moves on to the next one if a check fails.
+        // TODO: Once Rust 2027 arrives, `RangeInclusive` should be `Copy`, so remove `clone()` then.
+        self.source.try_random_checked(range.clone(), &mut |index, candidate| {
+            // A small step for the CPU, a huge step for resilience...
+            if !range.contains(&candidate) {
+                return Err(GenerateError::RandomnessOutOfRange);
             }
-            InnerHandle::Custom(source) => {
-                // TODO: dito
-                source.random(range.clone())
+
+            if let Some(health) = health {
+                if health.len() <= index {
+                    health.resize_with(index + 1, HealthTests::new);
+                }
+                let bits = u128::BITS - range.end().leading_zeros();
+                health[index]
+                    .check(candidate, bits)
+                    .map_err(GenerateError::HealthTestFailure)?;
             }
-        }?;
 
-        // A small step for the CPU, a huge step for resilience...
-        range.contains(&candidate).then_some(candidate)
+            Ok(())
+        })
     }
 }
 
-static GENERATOR: Mutex<Generator> = {
-    #[cfg(feature = "rand")]
-    let generator = Generator {
-        source: STANDARD_ENTROPY_SOURCE,
-        rng: None,
-        last_ulid: 0,
-    };
+#[cfg(any(feature = "rand", feature = "getrandom"))]
+fn since_epoch() -> Result<Duration, GenerateError> {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|_| GenerateError::ClockBeforeEpoch)
+}
+
+#[cfg(any(feature = "rand", feature = "getrandom"))]
+fn since_epoch_millis() -> Result<u64, GenerateError> {
+    u64::try_from(since_epoch()?.as_millis()).map_err(|_| GenerateError::TimestampOutOfRange)
+}
+
+/// Draws a uniformly distributed random number in the given range directly from the OS.
+///
+/// Uses rejection sampling on a bit mask covering the range, so at most half of
+/// the draws are discarded on average.
+#[cfg(feature = "getrandom")]
+fn os_random(range: RangeInclusive<u128>) -> Option<u128> {
+    let (start, end) = range.into_inner();
+    let span = end.checked_sub(start)?;
+    let mask = u128::MAX.checked_shr(span.leading_zeros()).unwrap_or(0);
+
+    loop {
+        let mut bytes = [0; 16];
+        getrandom::fill(&mut bytes).ok()?;
+
+        let candidate = u128::from_ne_bytes(bytes) & mask;
+        if candidate <= span {
+            return Some(start + candidate);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+type Lock<T> = Mutex<T>;
+
+#[cfg(not(feature = "std"))]
+type Lock<T> = critical_section::Mutex<RefCell<T>>;
+
+static GENERATOR: Lock<Generator> = {
+    #[cfg(any(feature = "rand", feature = "getrandom"))]
+    let generator = Generator::new(STANDARD_ENTROPY_SOURCE);
 
-    #[cfg(not(feature = "rand"))]
-    let generator = Generator {
-        source: NO_ENTROPY_SOURCE,
-        last_ulid: 0,
-    };
+    #[cfg(not(any(feature = "rand", feature = "getrandom")))]
+    let generator = Generator::new(NO_ENTROPY_SOURCE);
 
-    Mutex::new(generator)
+    #[cfg(feature = "std")]
+    let lock = Mutex::new(generator);
+
+    #[cfg(not(feature = "std"))]
+    let lock = critical_section::Mutex::new(RefCell::new(generator));
+
+    lock
 };
 
-pub(crate) fn generate() -> Option<u128> {
-    let mut generator = GENERATOR.lock().ok()?;
-    generator.generate()
+/// Runs `f` with exclusive access to the global generator.
+///
+/// Fails if the lock is poisoned (only possible with `std`).
+#[cfg(feature = "std")]
+fn with_generator<R>(f: impl FnOnce(&mut Generator) -> R) -> Result<R, GenerateError> {
+    let mut generator = GENERATOR.lock().map_err(|_| GenerateError::Poisoned)?;
+    Ok(f(&mut generator))
+}
+
+/// Runs `f` with exclusive access to the global generator.
+///
+/// Without `std`, the generator is protected by a critical section and cannot be poisoned.
+#[cfg(not(feature = "std"))]
+#[allow(clippy::unnecessary_wraps)] // Same signature as with `std`
+fn with_generator<R>(f: impl FnOnce(&mut Generator) -> R) -> Result<R, GenerateError> {
+    Ok(with_generator_recovered(f))
+}
+
+/// Runs `f` with exclusive access to the global generator, clearing a poisoned lock.
+#[cfg(feature = "std")]
+fn with_generator_recovered<R>(f: impl FnOnce(&mut Generator) -> R) -> R {
+    let mut generator = GENERATOR.lock().unwrap_or_else(|poisoned| {
+        GENERATOR.clear_poison();
+        poisoned.into_inner()
+    });
+
+    f(&mut generator)
+}
+
+/// Runs `f` with exclusive access to the global generator.
+#[cfg(not(feature = "std"))]
+fn with_generator_recovered<R>(f: impl FnOnce(&mut Generator) -> R) -> R {
+    critical_section::with(|cs| f(&mut GENERATOR.borrow_ref_mut(cs)))
+}
+
+pub(crate) fn generate() -> Result<u128, GenerateError> {
+    with_generator(Generator::generate)?
+}
+
+pub(crate) fn generate_range(count: NonZero<u64>) -> Result<(u128, u128), GenerateError> {
+    with_generator(|generator| generator.generate_range(count))?
 }
 
 /// Sets the entropy source for generating ULIDs.
 ///
 /// Sets a new entropy source and returns the previous set entropy source.
 ///
-/// Normally you don't need to call this function unless the `rand` crate is disabled,
+/// Normally you don't need to call this function unless both the `rand` and `getrandom` features are disabled,
 /// or if you're using a custom entropy source.
+///
+/// If health tests are enabled, their state is reset for the new entropy source.
+#[allow(clippy::must_use_candidate)] // Ignoring the previous entropy source is fine
 pub fn set_entropy_source(source: EntropySourceHandle) -> EntropySourceHandle {
-    let mut generator = GENERATOR.lock().unwrap_or_else(|poisoned| {
-        GENERATOR.clear_poison();
-        poisoned.into_inner()
+    with_generator_recovered(|generator| {
+        if generator.health.is_some() {
+            generator.health = Some(Vec::new());
+        }
+        core::mem::replace(&mut generator.source, source)
+    })
+}
+
+/// Enables or disables continuous health tests on random values.
+///
+/// The health tests follow the spirit of NIST SP 800-90B: A repetition count test detects
+/// entropy sources returning the same value again, and an adaptive proportion test detects
+/// entropy sources whose bits are heavily biased (e.g. stuck at zero).
+/// The probability of a false alarm is below 2<sup>-40</sup> per 1024 random bits.
+///
+/// When a test fails, ULID generation is refused (e.g. [`Ulid::try_new()`](crate::Ulid::try_new) returns `None`)
+/// until the health tests are enabled again, or a new entropy source is set.
+/// The reason of the failure can be queried with [`health_test_failure()`].
+/// The entropy sources of a fallback chain are tested each on their own, and one failing is skipped
+/// in favor of the next.
+///
+/// Health tests are disabled by default. Enabling them always starts with a fresh state.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::Ulid;
+///
+/// mr_ulid::set_health_tests(true);
+///
+/// assert!(Ulid::try_new().is_some());
+/// assert_eq!(mr_ulid::health_test_failure(), None);
+/// ```
+pub fn set_health_tests(enabled: bool) {
+    with_generator_recovered(|generator| {
+        generator.health = enabled.then(Vec::new);
     });
+}
 
-    std::mem::replace(&mut generator.source, source)
+/// Limits how far timestamps of generated ULIDs may run ahead of the entropy source's clock.
+///
+/// To keep ULIDs strictly monotonic, the timestamp of the last generated ULID is reused when the clock
+/// goes backwards, and it may spill over into the next millisecond when a huge number of ULIDs is
+/// generated within a single millisecond. In both cases, generated timestamps run ahead of the clock.
+/// A clock which once returned a bogus timestamp far in the future even lets all following
+/// ULIDs carry that future timestamp.
+///
+/// With a bound set, generation fails (e.g. [`Ulid::try_new()`](crate::Ulid::try_new) returns `None`)
+/// instead of producing a ULID whose timestamp is further ahead of the clock than `max_ahead`.
+/// Generation succeeds again once the clock has caught up, or after [`reset_high_water_mark()`].
+///
+/// The bound is disabled (`None`) by default. Durations are truncated to whole milliseconds.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use mr_ulid::Ulid;
+///
+/// mr_ulid::set_max_ahead_of_clock(Some(Duration::from_secs(1)));
+///
+/// assert!(Ulid::try_new().is_some());
+/// ```
+pub fn set_max_ahead_of_clock(max_ahead: Option<Duration>) {
+    let millis = max_ahead.map(|max_ahead| u64::try_from(max_ahead.as_millis()).unwrap_or(u64::MAX));
+
+    with_generator_recovered(|generator| {
+        generator.max_ahead = millis;
+    });
 }
 
-#[cfg(feature = "rand")]
+/// Forgets the last generated ULID, the high-water mark for monotonicity.
+///
+/// After a bogus timestamp far in the future, all following ULIDs would carry that timestamp
+/// to stay monotonic (or fail, see [`set_max_ahead_of_clock()`]). Resetting the high-water mark
+/// lets the generator start over with the timestamps of the clock.
+///
+/// Note that ULIDs generated after the reset may be smaller than ULIDs generated before the reset,
+/// so monotonicity is only guaranteed among ULIDs generated after the reset.
+///
+/// A shared state file (see `set_shared_state_file()`) is not reset. Truncate the file
+/// to reset the high-water mark for all processes.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::Ulid;
+///
+/// mr_ulid::reset_high_water_mark();
+///
+/// assert!(Ulid::try_new().is_some());
+/// ```
+pub fn reset_high_water_mark() {
+    with_generator_recovered(|generator| {
+        generator.last_ulid = 0;
+    });
+}
+
+/// Enables or disables sub-millisecond precision for generated ULIDs.
+///
+/// When enabled, the top 12 bits of the random component of generated ULIDs store the fraction
+/// of the millisecond (in units of 1/4096 ms, about 244 ns), and only the remaining 68 bits are random.
+/// ULIDs generated within the same millisecond are then ordered by their finer timestamp, which can
+/// be read with [`Ulid::timestamp_nanos()`](crate::Ulid::timestamp_nanos).
+///
+/// The sub-millisecond timestamp is taken from [`EntropySource::timestamp_nanos()`].
+/// The standard entropy source uses the system clock with its full precision.
+///
+/// Strict monotonicity is maintained in either mode, also when switching modes.
+/// Sub-millisecond precision is disabled by default.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::Ulid;
+///
+/// mr_ulid::set_sub_millisecond_precision(true);
+///
+/// let u = Ulid::new();
+///
+/// assert_eq!(u.timestamp_nanos() / 1_000_000, u128::from(u.timestamp()));
+/// ```
+pub fn set_sub_millisecond_precision(enabled: bool) {
+    with_generator_recovered(|generator| {
+        generator.sub_millis = enabled;
+    });
+}
+
+/// Shares the last generated ULID with other processes on the same host via a file.
+///
+/// Within a process, generated ULIDs are always strictly monotonic. With a shared state file,
+/// this guarantee extends to all processes using the same file: Every generation locks the file
+/// exclusively, continues after the last ULID stored in the file, and stores the new ULID.
+/// The file is created if it does not exist.
+///
+/// Generation is considerably slower with a shared state file, as each ULID needs file locking
+/// and I/O. If the file cannot be locked, read, or written, or does not contain a valid ULID,
+/// generation fails (e.g. [`Ulid::try_new()`](crate::Ulid::try_new) returns `None`).
+///
+/// The file is not synced to disk, so the guarantee may not hold across a crash of the host.
+///
+/// Passing `None` stops sharing; the current process continues monotonically.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or created. The previous setting is kept then.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use mr_ulid::Ulid;
+///
+/// let path = std::env::temp_dir().join("my-app.ulid");
+///
+/// mr_ulid::set_shared_state_file(Some(&path))?;
+///
+/// let u1 = Ulid::new(); // In this process
+/// let u2 = Ulid::new(); // Possibly in another process
+///
+/// assert!(u1 < u2);
+/// # mr_ulid::set_shared_state_file(None)?;
+/// # std::fs::remove_file(path)?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "shared-state")]
+pub fn set_shared_state_file(path: Option<&Path>) -> io::Result<()> {
+    let shared = path.map(SharedState::open).transpose()?;
+
+    with_generator_recovered(|generator| {
+        generator.shared = shared;
+    });
+
+    Ok(())
+}
+
+/// Returns the reason, why the continuous health tests failed.
+///
+/// Returns `None` if the health tests are disabled or did not fail.
+/// With a fallback chain, the failure of the first failed entropy source is returned,
+/// even if generation continues with another one. See [`set_health_tests()`] for details.
+#[must_use]
+pub fn health_test_failure() -> Option<HealthTestFailure> {
+    with_generator_recovered(|generator| generator.health.as_ref()?.iter().find_map(HealthTests::failure))
+}
+
+/// Returns the index of the entropy source which delivered the random values of the last generated ULID.
+///
+/// Only fallback chains (see [`EntropySourceHandle::fallback()`]) report the index, so this function
+/// returns `None` for all other entropy sources. It also returns `None` if no random value was requested
+/// from the fallback chain yet, or all of its entropy sources failed.
+#[must_use]
+pub fn used_entropy_source() -> Option<usize> {
+    with_generator_recovered(|generator| generator.source.used())
+}
+
+#[cfg(any(feature = "rand", feature = "getrandom"))]
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Ulid;
+    use crate::{Ulid, util};
 
     fn manipulate_generator_last_ulid(last_id: u128) {
         let mut generator = GENERATOR.lock().unwrap();
@@ -314,6 +886,310 @@ mod tests {
         assert_eq!(u6.to_u128(), u128::MAX);
 
         assert!(Ulid::try_new().is_none());
+        assert_eq!(Ulid::try_generate(), Err(GenerateError::Exhausted));
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn test_os_random() {
+        assert_eq!(os_random(5..=5), Some(5));
+        assert_eq!(os_random(0..=u128::MAX).map(|_| ()), Some(()));
+
+        for _ in 0..1000 {
+            let n = os_random(10..=13).unwrap();
+            assert!((10..=13).contains(&n));
+        }
+    }
+
+    #[test]
+    fn test_health_tests() {
+        struct ConstantSource(u64);
+        impl EntropySource for ConstantSource {
+            fn timestamp(&mut self) -> Option<u64> {
+                self.0 += 1;
+                Some(self.0)
+            }
+            fn random(&mut self, _range: RangeInclusive<u128>) -> Option<u128> {
+                Some(0x0123_4567_89AB_CDEF_0123)
+            }
+        }
+
+        let mut generator = Generator::new(EntropySourceHandle::new(ConstantSource(0)));
+
+        assert!(generator.generate().is_ok());
+        assert!(generator.generate().is_ok());
+
+        generator.health = Some(Vec::new());
+
+        let failure = Err(GenerateError::HealthTestFailure(HealthTestFailure::RepetitionCount));
+        assert!(generator.generate().is_ok());
+        assert_eq!(generator.generate(), failure);
+        assert_eq!(generator.generate(), failure);
+
+        let failure = generator.health.as_ref().and_then(|health| health[0].failure());
+        assert_eq!(failure, Some(HealthTestFailure::RepetitionCount));
+    }
+
+    #[test]
+    fn test_max_ahead_of_clock() {
+        struct SequenceSource(std::vec::IntoIter<u64>);
+        impl EntropySource for SequenceSource {
+            fn timestamp(&mut self) -> Option<u64> {
+                self.0.next()
+            }
+            fn random(&mut self, _range: RangeInclusive<u128>) -> Option<u128> {
+                Some(1)
+            }
+        }
+
+        let timestamps = vec![1_000_000, 1000, 1000, 1003, 1004, 1005];
+        let mut generator = Generator::new(EntropySourceHandle::new(SequenceSource(timestamps.into_iter())));
+        generator.max_ahead = Some(1);
+
+        // Bogus timestamp from the future
+        let u1 = generator.generate().unwrap();
+        assert_eq!(u1 >> RANDOM_BITS, 1_000_000);
+
+        // Clock is back to normal, but generator is poisoned by the bogus timestamp
+        assert_eq!(generator.generate(), Err(GenerateError::AheadOfClock));
+
+        generator.last_ulid = 0; // as done by `reset_high_water_mark()`
+
+        let u2 = generator.generate().unwrap();
+        assert_eq!(u2 >> RANDOM_BITS, 1000);
+
+        // Spill over into next milliseconds is limited too
+        generator.last_ulid = (1005 << RANDOM_BITS) | RANDOM_MASK;
+
+        assert_eq!(generator.generate(), Err(GenerateError::AheadOfClock)); // 1006 is 3ms ahead of 1003
+        assert_eq!(generator.generate(), Err(GenerateError::AheadOfClock)); // 1006 is 2ms ahead of 1004
+        assert_eq!(generator.generate().map(|u| u >> RANDOM_BITS), Ok(1006)); // 1006 is 1ms ahead of 1005
+    }
+
+    #[cfg(feature = "shared-state")]
+    #[test]
+    fn test_shared_state() {
+        let path = std::env::temp_dir().join(format!("mr-ulid-test-shared-state-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // Two generators with their own state, like in different processes
+        let mut generator1 = Generator::new(STANDARD_ENTROPY_SOURCE);
+        let mut generator2 = Generator::new(STANDARD_ENTROPY_SOURCE);
+
+        generator1.shared = Some(SharedState::open(&path).unwrap());
+        generator2.shared = Some(SharedState::open(&path).unwrap());
+
+        // Second generator runs in the future for some reason
+        generator2.last_ulid = u128::from(TIMESTAMP_MAX - 1) << RANDOM_BITS;
+
+        let u1 = generator2.generate().unwrap();
+        let u2 = generator1.generate().unwrap();
+        let u3 = generator2.generate().unwrap();
+        let u4 = generator1.generate().unwrap();
+
+        assert!(u1 < u2);
+        assert!(u2 < u3);
+        assert!(u3 < u4);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sub_millisecond_precision() {
+        struct NanosSource(std::vec::IntoIter<u128>);
+        impl EntropySource for NanosSource {
+            fn timestamp(&mut self) -> Option<u64> {
+                unreachable!()
+            }
+            fn random(&mut self, range: RangeInclusive<u128>) -> Option<u128> {
+                Some(*range.end())
+            }
+            fn timestamp_nanos(&mut self) -> Option<u128> {
+                self.0.next()
+            }
+        }
+
+        let nanos = vec![
+            7_000_250_000,
+            7_000_250_100,
+            7_000_500_000,
+            7_000_400_000,
+            7_000_999_999,
+        ];
+        let mut generator = Generator::new(EntropySourceHandle::new(NanosSource(nanos.into_iter())));
+        generator.sub_millis = true;
+
+        let u1 = generator.generate().unwrap();
+        assert_eq!(util::timestamp_nanos(u1), 7_000_250_000);
+        assert_eq!(
+            u1 & RANDOM_MASK,
+            (1024 << SUB_MILLIS_RANDOM_BITS) | SUB_MILLIS_RANDOM_GEN_MAX
+        );
+
+        // Same 1/4096 ms as before: increment
+        let u2 = generator.generate().unwrap();
+        assert_eq!(u2, u1 + 1);
+
+        let u3 = generator.generate().unwrap();
+        assert_eq!(util::timestamp_nanos(u3), 7_000_500_000);
+
+        // Clock goes backwards: increment
+        let u4 = generator.generate().unwrap();
+        assert_eq!(u4, u3 + 1);
+
+        let u5 = generator.generate().unwrap();
+        assert_eq!(u5 >> RANDOM_BITS, 7000);
+        assert_eq!(util::timestamp_nanos(u5), 7_000_999_755);
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        use std::sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        };
+
+        struct FlakySource(Arc<AtomicBool>);
+        impl EntropySource for FlakySource {
+            fn timestamp(&mut self) -> Option<u64> {
+                self.0.load(Ordering::Relaxed).then_some(2000)
+            }
+            fn random(&mut self, _range: RangeInclusive<u128>) -> Option<u128> {
+                self.0.load(Ordering::Relaxed).then_some(0x2A)
+            }
+        }
+
+        let available = Arc::new(AtomicBool::new(true));
+        let primary = EntropySourceHandle::new(FlakySource(Arc::clone(&available)));
+        let secondary = EntropySourceHandle::new(FixedEntropySource {
+            timestamp: 1000,
+            random: 0x7,
+        });
+
+        let mut generator = Generator::new(EntropySourceHandle::fallback([primary, secondary]));
+        assert_eq!(generator.source.used(), None);
+
+        let ulid = generator.next_ulid().unwrap();
+        assert_eq!(ulid, (2000 << RANDOM_BITS) | 0x2A);
+        assert_eq!(generator.source.used(), Some(0));
+
+        available.store(false, Ordering::Relaxed);
+        generator.last_ulid = 0;
+        let ulid = generator.next_ulid().unwrap();
+        assert_eq!(ulid, (1000 << RANDOM_BITS) | 0x7);
+        assert_eq!(generator.source.used(), Some(1));
+
+        // The chain returns to the primary source once it recovers
+        available.store(true, Ordering::Relaxed);
+        assert!(generator.next_ulid().is_ok());
+        assert_eq!(generator.source.used(), Some(0));
+
+        let mut generator = Generator::new(EntropySourceHandle::fallback([NO_ENTROPY_SOURCE, NO_ENTROPY_SOURCE]));
+        assert_eq!(generator.next_ulid(), Err(GenerateError::NoEntropySource));
+        assert_eq!(generator.source.used(), None);
+
+        assert_eq!(EntropySourceHandle::fallback([]).timestamp(), None);
+    }
+
+    #[test]
+    fn test_fallback_chain_checks() {
+        struct CountingSource(u128);
+        impl EntropySource for CountingSource {
+            fn timestamp(&mut self) -> Option<u64> {
+                Some(1000)
+            }
+            fn random(&mut self, _range: RangeInclusive<u128>) -> Option<u128> {
+                self.0 += 1;
+                Some(self.0)
+            }
+        }
+
+        let broken = EntropySourceHandle::new(FixedEntropySource {
+            timestamp: 1000,
+            random: u128::MAX,
+        });
+        let constant = EntropySourceHandle::new(FixedEntropySource {
+            timestamp: 1000,
+            random: 0x2A,
+        });
+        let counting = EntropySourceHandle::new(CountingSource(0x100));
+
+        // Randomness out of range moves on to the next entropy source
+        let mut generator = Generator::new(EntropySourceHandle::fallback([broken, constant, counting]));
+        generator.health = Some(Vec::new());
+        assert_eq!(generator.next_ulid(), Ok((1000 << RANDOM_BITS) | 0x2A));
+        assert_eq!(generator.source.used(), Some(1));
+
+        // So does failing the health tests, and the failed entropy source stays skipped
+        for random in 0x101..0x104 {
+            generator.last_ulid = 0;
+            assert_eq!(generator.next_ulid(), Ok((1000 << RANDOM_BITS) | random));
+            assert_eq!(generator.source.used(), Some(2));
+        }
+
+        let failures: Vec<_> = generator.health.iter().flatten().map(HealthTests::failure).collect();
+        assert_eq!(failures, [None, Some(HealthTestFailure::RepetitionCount), None]);
+    }
+
+    #[test]
+    fn test_generate_range() {
+        let source = EntropySourceHandle::new(FixedEntropySource {
+            timestamp: 1000,
+            random: 0x10,
+        });
+        let mut generator = Generator::new(source);
+
+        let start = 1000 << RANDOM_BITS | 0x10;
+        let count = NonZero::new(0x100).unwrap();
+        assert_eq!(generator.generate_range(count), Ok((start, start + 0xFF)));
+        assert_eq!(generator.generate(), Ok(start + 0x100));
+
+        // Ranges end with the millisecond
+        generator.last_ulid = (1000 << RANDOM_BITS) | (RANDOM_MASK - 0x10);
+        let end = (1000 << RANDOM_BITS) | RANDOM_MASK;
+        assert_eq!(generator.generate_range(NonZero::<u64>::MAX), Ok((end - 0xF, end)));
+        assert_eq!(generator.generate(), Ok(1001 << RANDOM_BITS));
+    }
+
+    #[test]
+    fn test_generate_errors() {
+        struct TestSource {
+            timestamp: Option<u64>,
+            random: Option<u128>,
+        }
+        impl EntropySource for TestSource {
+            fn timestamp(&mut self) -> Option<u64> {
+                self.timestamp
+            }
+            fn random(&mut self, _range: RangeInclusive<u128>) -> Option<u128> {
+                self.random
+            }
+        }
+
+        let generate = |timestamp, random| {
+            let source = EntropySourceHandle::new(TestSource { timestamp, random });
+            Generator::new(source).generate()
+        };
+
+        assert_eq!(generate(None, Some(1)), Err(GenerateError::TimestampUnavailable));
+        assert_eq!(
+            generate(Some(TIMESTAMP_MAX), Some(1)),
+            Err(GenerateError::TimestampOutOfRange)
+        );
+        assert_eq!(generate(Some(1), None), Err(GenerateError::RandomUnavailable));
+        assert_eq!(generate(Some(1), Some(0)), Err(GenerateError::RandomnessOutOfRange));
+        assert_eq!(generate(Some(1), Some(1)), Ok((1 << RANDOM_BITS) | 1));
+
+        assert_eq!(
+            Generator::new(NO_ENTROPY_SOURCE).generate(),
+            Err(GenerateError::NoEntropySource)
+        );
+
+        assert_eq!(GenerateError::NoEntropySource.to_string(), "no entropy source set");
+        assert_eq!(
+            GenerateError::HealthTestFailure(HealthTestFailure::AdaptiveProportion).to_string(),
+            "entropy source failed health tests"
+        );
     }
 
     #[test]
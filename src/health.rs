@@ -0,0 +1,198 @@
+use core::fmt;
+
+/// Consecutive identical random values tolerated by the repetition count test.
+///
+/// Every random value carries close to 80 bits of entropy, so even a single immediate
+/// repetition has a probability below 2<sup>-79</sup> and is treated as a failure.
+const REPETITION_CUTOFF: u32 = 2;
+
+/// Number of bits observed by the adaptive proportion test per window.
+const PROPORTION_WINDOW: u32 = 1024;
+
+/// Occurrences of the first bit of a window which let the adaptive proportion test fail.
+///
+/// This is the cutoff for binary samples with full entropy and a false positive
+/// probability of 2<sup>-40</sup> per window (NIST SP 800-90B, section 4.4.2).
+const PROPORTION_CUTOFF: u32 = 626;
+
+/// Failures of the continuous health tests on random values.
+///
+/// Health tests are disabled by default and can be enabled with [`set_health_tests()`](crate::set_health_tests).
+/// Once a test failed, no more ULIDs are generated until the health tests are enabled again,
+/// or a new entropy source is set with [`set_entropy_source()`](crate::set_entropy_source).
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum HealthTestFailure {
+    /// The entropy source returned the same random value repeatedly.
+    RepetitionCount,
+    /// The bits of the random values are heavily biased towards zero or one.
+    AdaptiveProportion,
+}
+
+impl core::error::Error for HealthTestFailure {}
+
+impl fmt::Display for HealthTestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match *self {
+            Self::RepetitionCount => "entropy source failed repetition count test",
+            Self::AdaptiveProportion => "entropy source failed adaptive proportion test",
+        };
+        write!(f, "{message}")
+    }
+}
+
+/// Continuous health tests in the spirit of NIST SP 800-90B, section 4.4.
+///
+/// The repetition count test operates on whole random values, while the
+/// adaptive proportion test operates on the individual bits of the values.
+pub(crate) struct HealthTests {
+    last_value: Option<u128>,
+    repetitions: u32,
+    window_first: bool,
+    window_matches: u32,
+    window_len: u32,
+    failure: Option<HealthTestFailure>,
+}
+
+impl HealthTests {
+    pub(crate) const fn new() -> Self {
+        Self {
+            last_value: None,
+            repetitions: 0,
+            window_first: false,
+            window_matches: 0,
+            window_len: 0,
+            failure: None,
+        }
+    }
+
+    pub(crate) const fn failure(&self) -> Option<HealthTestFailure> {
+        self.failure
+    }
+
+    /// Feeds a random value into the tests, of which the lowest `bits` are random.
+    ///
+    /// A failure is sticky: all following calls fail with the same reason.
+    pub(crate) fn check(&mut self, value: u128, bits: u32) -> Result<(), HealthTestFailure> {
+        if let Some(failure) = self.failure {
+            return Err(failure);
+        }
+
+        let result = self
+            .repetition_count(value)
+            .and_then(|()| self.adaptive_proportion(value, bits));
+
+        if let Err(failure) = result {
+            self.failure = Some(failure);
+        }
+
+        result
+    }
+
+    fn repetition_count(&mut self, value: u128) -> Result<(), HealthTestFailure> {
+        if self.last_value == Some(value) {
+            self.repetitions += 1;
+        } else {
+            self.last_value = Some(value);
+            self.repetitions = 1;
+        }
+
+        if self.repetitions >= REPETITION_CUTOFF {
+            Err(HealthTestFailure::RepetitionCount)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn adaptive_proportion(&mut self, value: u128, bits: u32) -> Result<(), HealthTestFailure> {
+        for i in 0..bits {
+            let bit = (value >> i) & 1 == 1;
+
+            if self.window_len == 0 {
+                self.window_first = bit;
+                self.window_matches = 1;
+            } else if bit == self.window_first {
+                self.window_matches += 1;
+                if self.window_matches >= PROPORTION_CUTOFF {
+                    return Err(HealthTestFailure::AdaptiveProportion);
+                }
+            }
+
+            self.window_len = (self.window_len + 1) % PROPORTION_WINDOW;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RANDOM_BITS;
+
+    /// `SplitMix64`, good enough to feed the health tests with unbiased bits.
+    struct SplitMix(u64);
+
+    impl SplitMix {
+        fn next(&mut self) -> u128 {
+            let mut next = || {
+                self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                let mut z = self.0;
+                z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                z ^ (z >> 31)
+            };
+            ((u128::from(next()) << 64) | u128::from(next())) & ((1 << RANDOM_BITS) - 1)
+        }
+    }
+
+    #[test]
+    fn test_random_values_pass() {
+        let mut rng = SplitMix(42);
+        let mut tests = HealthTests::new();
+
+        for _ in 0..100_000 {
+            assert_eq!(tests.check(rng.next(), RANDOM_BITS), Ok(()));
+        }
+        assert_eq!(tests.failure(), None);
+    }
+
+    #[test]
+    fn test_repetition_count() {
+        let mut tests = HealthTests::new();
+
+        assert_eq!(tests.check(0x1234_5678_9ABC_DEF0_1234, RANDOM_BITS), Ok(()));
+        assert_eq!(
+            tests.check(0x1234_5678_9ABC_DEF0_1234, RANDOM_BITS),
+            Err(HealthTestFailure::RepetitionCount)
+        );
+        assert_eq!(tests.failure(), Some(HealthTestFailure::RepetitionCount));
+
+        // Failures are sticky
+        assert_eq!(
+            tests.check(0x5555, RANDOM_BITS),
+            Err(HealthTestFailure::RepetitionCount)
+        );
+    }
+
+    #[test]
+    fn test_adaptive_proportion() {
+        let mut tests = HealthTests::new();
+
+        let results: Vec<_> = (1..=100).map(|n| tests.check(n, RANDOM_BITS)).collect();
+
+        assert!(results.contains(&Err(HealthTestFailure::AdaptiveProportion)));
+        assert_eq!(tests.failure(), Some(HealthTestFailure::AdaptiveProportion));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            HealthTestFailure::RepetitionCount.to_string(),
+            "entropy source failed repetition count test"
+        );
+        assert_eq!(
+            HealthTestFailure::AdaptiveProportion.to_string(),
+            "entropy source failed adaptive proportion test"
+        );
+    }
+}
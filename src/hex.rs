@@ -0,0 +1,44 @@
+#[cfg(feature = "alloc")]
+use core::str::from_utf8_unchecked;
+
+use crate::{Error, util};
+
+/// Offsets of the hyphens in the 8-4-4-4-12 form.
+const HYPHENS: [usize; 4] = [8, 13, 18, 23];
+
+#[cfg(feature = "alloc")]
+pub fn encode(mut n: u128, buffer: &mut [u8; 36]) -> &str {
+    const DIGITS: [u8; 16] = *b"0123456789abcdef";
+
+    for (offset, byte) in buffer.iter_mut().enumerate().rev() {
+        if HYPHENS.contains(&offset) {
+            *byte = b'-';
+        } else {
+            *byte = DIGITS[(n & 0xF) as usize];
+            n >>= 4;
+        }
+    }
+
+    // Safety: Encoding above guarantees valid UTF-8
+    unsafe { from_utf8_unchecked(buffer) }
+}
+
+pub fn decode(bytes: &[u8]) -> Result<u128, Error> {
+    let bytes: &[u8; 36] = util::as_array(bytes)?;
+    let mut n = 0;
+
+    for (offset, &byte) in bytes.iter().enumerate() {
+        if HYPHENS.contains(&offset) {
+            if byte != b'-' {
+                return Err(Error::InvalidChar { offset, byte });
+            }
+        } else {
+            let digit = char::from(byte)
+                .to_digit(16)
+                .ok_or(Error::InvalidChar { offset, byte })?;
+            n = (n << 4) | u128::from(digit);
+        }
+    }
+
+    Ok(n)
+}
@@ -0,0 +1,195 @@
+#[cfg(feature = "alloc")]
+use core::str::from_utf8_unchecked;
+
+#[cfg(feature = "alloc")]
+use crate::base32;
+use crate::{Error, ParseOptions, Typo, parse};
+
+/// Length of the human-readable form: 26 characters in groups of 5, 5 hyphens, and the check symbol.
+#[cfg(feature = "alloc")]
+pub const LEN: usize = 32;
+
+/// Crockford's Base32 alphabet, extended by the 5 check symbols for the values 32 to 36.
+// cspell:disable-next-line
+const CHECK_SYMBOLS: [u8; 37] = *b"0123456789ABCDEFGHJKMNPQRSTVWXYZ*~$=U";
+
+/// Groups of characters which are easily confused when read aloud or written by hand.
+const CONFUSABLE: [&[u8]; 12] = [
+    b"BDPTV", // Rhyme with "ee"
+    b"E3", b"FS", // "eff" and "ess"
+    b"MN", b"59", // "five" and "nine"
+    b"0DQ", b"17", b"2Z", b"5S", b"6G", b"8B", b"UV",
+];
+
+/// Encodes `n` in groups of 5 characters, separated by hyphens, followed by the check symbol.
+#[cfg(feature = "alloc")]
+pub fn encode(n: u128, buffer: &mut [u8; LEN]) -> &str {
+    let mut ulid = [0; 26];
+    base32::encode(n, &mut ulid);
+
+    for (group, chunk) in buffer.chunks_mut(6).zip(ulid.chunks(5)) {
+        group[..chunk.len()].copy_from_slice(chunk);
+        if let Some(hyphen) = group.get_mut(5) {
+            *hyphen = b'-';
+        }
+    }
+    buffer[LEN - 1] = CHECK_SYMBOLS[check_value(n)];
+
+    // Safety: Encoding above guarantees valid UTF-8
+    unsafe { from_utf8_unchecked(buffer) }
+}
+
+/// A ULID string with hyphens anywhere, followed by a check symbol.
+struct Checked<'a> {
+    /// The ULID string, without the check symbol.
+    ulid: &'a str,
+    /// The value of the ULID.
+    n: u128,
+    /// The value of the check symbol.
+    check: usize,
+    /// The byte offset of the check symbol in the input.
+    offset: usize,
+    /// The check symbol in the input.
+    byte: u8,
+}
+
+impl<'a> Checked<'a> {
+    fn parse(input: &'a str) -> Result<Self, Error> {
+        let Some((offset, c)) = input.char_indices().next_back() else {
+            return Err(Error::TooShort {
+                expected: 26,
+                actual: 0,
+            });
+        };
+        let ulid = &input[..offset];
+        let n = parse::parse(ulid, ParseOptions::DEFAULT.ignore_hyphens(true))?;
+
+        let byte = input.as_bytes()[offset];
+        let check = decode_check_symbol(c).ok_or(Error::InvalidChar { offset, byte })?;
+
+        Ok(Self {
+            ulid,
+            n,
+            check,
+            offset,
+            byte,
+        })
+    }
+
+    /// Collects all single-character corrections between confusable characters which fix the check symbol.
+    fn typo_candidates(&self) -> TypoCandidates {
+        let mut candidates = TypoCandidates::default();
+        let positions = self.ulid.bytes().enumerate().filter(|&(_, byte)| byte != b'-');
+
+        for (index, (offset, byte)) in positions.enumerate() {
+            let shift = 5 * (25 - index);
+            let found = digit(self.n >> shift);
+            let max = if index == 0 { 7 } else { 31 };
+
+            for expected in (0..=max).filter(|&expected| expected != found) {
+                let corrected = (self.n & !(0x1F << shift)) | ((expected as u128) << shift);
+                if check_value(corrected) == self.check && is_confusable(found, expected) {
+                    candidates.push(Typo::new(offset, byte, CHECK_SYMBOLS[expected]));
+                }
+            }
+        }
+
+        let expected = check_value(self.n);
+        if is_confusable(self.check, expected) {
+            candidates.push(Typo::new(self.offset, self.byte, CHECK_SYMBOLS[expected]));
+        }
+
+        candidates
+    }
+}
+
+/// Decodes a ULID with hyphens anywhere, followed by the check symbol, and verifies the check symbol.
+pub fn decode(input: &str) -> Result<u128, Error> {
+    let checked = Checked::parse(input)?;
+
+    if checked.check == check_value(checked.n) {
+        Ok(checked.n)
+    } else {
+        let mut candidates = checked.typo_candidates();
+        let typo = candidates.next().filter(|_| candidates.next().is_none());
+        Err(Error::ChecksumMismatch { typo })
+    }
+}
+
+/// Returns the single-character corrections between confusable characters which fix the check symbol.
+///
+/// Returns no corrections if the check symbol matches, or the input is malformed.
+/// [`Ulid::from_human_str()`](crate::Ulid::from_human_str) reports the correction only if it is unique.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::typo_candidates;
+///
+// cspell:disable-next-line
+/// let typos: Vec<_> = typo_candidates("01JBX-69SQY-BD54B-R1ZV3-S8PX9-2D").collect();
+///
+/// assert_eq!(typos.len(), 3);
+/// assert!(typos.iter().any(|typo| typo.offset() == 9 && typo.expected() == b'0'));
+/// ```
+#[must_use]
+pub fn typo_candidates(input: &str) -> TypoCandidates {
+    match Checked::parse(input) {
+        Ok(checked) if checked.check != check_value(checked.n) => checked.typo_candidates(),
+        _ => TypoCandidates::default(),
+    }
+}
+
+/// Returns the value of the low 5 bits of `n`.
+const fn digit(n: u128) -> usize {
+    (n & 0x1F) as usize
+}
+
+/// Returns the value of the check symbol for `n`.
+const fn check_value(n: u128) -> usize {
+    // The remainder is less than 37
+    #[allow(clippy::cast_possible_truncation)]
+    let check = (n % 37) as usize;
+    check
+}
+
+/// Decodes a check symbol, case-insensitive and with the aliases `I`, `L` and `O` of the digits.
+fn decode_check_symbol(c: char) -> Option<usize> {
+    match c.to_ascii_uppercase() {
+        'I' | 'L' => Some(1),
+        'O' => Some(0),
+        c => CHECK_SYMBOLS.iter().position(|&symbol| char::from(symbol) == c),
+    }
+}
+
+/// Returns `true` if the characters of the values `a` and `b` are easily confused.
+fn is_confusable(a: usize, b: usize) -> bool {
+    let (a, b) = (CHECK_SYMBOLS[a], CHECK_SYMBOLS[b]);
+    a != b && CONFUSABLE.iter().any(|group| group.contains(&a) && group.contains(&b))
+}
+
+/// An iterator over likely typos in a ULID with check symbol, created by [`typo_candidates()`](crate::typo_candidates).
+#[derive(Debug, Clone, Default)]
+pub struct TypoCandidates {
+    typos: [Option<Typo>; 27],
+    len: usize,
+    next: usize,
+}
+
+impl TypoCandidates {
+    const fn push(&mut self, typo: Typo) {
+        // At most one correction per character, as 32 and 37 are coprime
+        self.typos[self.len] = Some(typo);
+        self.len += 1;
+    }
+}
+
+impl Iterator for TypoCandidates {
+    type Item = Typo;
+
+    fn next(&mut self) -> Option<Typo> {
+        let typo = self.typos.get(self.next).copied().flatten()?;
+        self.next += 1;
+        Some(typo)
+    }
+}
@@ -0,0 +1,184 @@
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use crate::{Error, Ulid, util};
+
+/// A ULID tagged with the type of the entity it identifies.
+///
+/// `Id<User>` and `Id<Order>` are distinct types, so the IDs of different entities can't be mixed up.
+/// The type parameter is only a marker: All traits of `Ulid` are implemented regardless of `T`,
+/// so `Id<T>` is `Copy`, `Ord`, `Hash`, `Send` and `Sync` even if `T` isn't.
+///
+/// `Id<T>` has the same size as `Ulid`, and `Option<Id<T>>` too. It formats, parses and (with the `serde` feature)
+/// serializes exactly like a `Ulid`. Use [`PrefixedUlid`](crate::PrefixedUlid) if the type should be visible
+/// in the string form.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::{Id, Ulid};
+///
+/// struct User;
+/// struct Order;
+///
+/// fn find_user(id: Id<User>) -> Option<String> {
+///     # let _ = id;
+///     // ...
+///     # None
+/// }
+///
+/// let user_id = Id::<User>::new();
+/// let order_id = Id::<Order>::new();
+///
+/// find_user(user_id);
+/// // find_user(order_id); // Doesn't compile
+///
+/// let s = user_id.to_string();
+/// let parsed: Id<User> = s.parse()?;
+/// assert_eq!(parsed, user_id);
+/// assert_eq!(Ulid::from(parsed).to_string(), s);
+///
+/// assert_eq!(size_of::<Option<Id<User>>>(), size_of::<Ulid>());
+/// # Ok::<(), mr_ulid::Error>(())
+/// ```
+pub struct Id<T> {
+    ulid: Ulid,
+    entity: PhantomData<fn() -> T>,
+}
+
+impl<T> Id<T> {
+    /// Generates a new unique `Id`, like [`Ulid::new()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ULID can't be generated, see [`Ulid::new()`].
+    #[cfg(any(feature = "std", feature = "critical-section"))]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_ulid(Ulid::new())
+    }
+
+    /// Creates an `Id` from a `Ulid`.
+    #[must_use]
+    pub const fn from_ulid(ulid: Ulid) -> Self {
+        Self {
+            ulid,
+            entity: PhantomData,
+        }
+    }
+
+    /// Returns the `Ulid` of the `Id`.
+    #[must_use]
+    pub const fn ulid(self) -> Ulid {
+        self.ulid
+    }
+}
+
+#[cfg(any(feature = "std", feature = "critical-section"))]
+impl<T> Default for Id<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ulid == other.ulid
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ulid.cmp(&other.ulid)
+    }
+}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ulid.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        util::debug_ulid("Id", self.ulid.to_u128(), f)
+    }
+}
+
+impl<T> fmt::Display for Id<T> {
+    /// Formats like [`Ulid`], honoring width, fill, alignment and the alternate flag (`{:#}`) for lowercase.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.ulid, f)
+    }
+}
+
+impl<T> fmt::LowerHex for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.ulid, f)
+    }
+}
+
+impl<T> fmt::UpperHex for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.ulid, f)
+    }
+}
+
+impl<T> fmt::Binary for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Binary::fmt(&self.ulid, f)
+    }
+}
+
+impl<T> FromStr for Id<T> {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self::from_ulid)
+    }
+}
+
+impl<T> From<Ulid> for Id<T> {
+    fn from(ulid: Ulid) -> Self {
+        Self::from_ulid(ulid)
+    }
+}
+
+impl<T> From<Id<T>> for Ulid {
+    fn from(id: Id<T>) -> Self {
+        id.ulid
+    }
+}
+
+impl<T> From<Id<T>> for u128 {
+    fn from(id: Id<T>) -> Self {
+        id.ulid.to_u128()
+    }
+}
+
+impl<T> TryFrom<u128> for Id<T> {
+    type Error = Error;
+    fn try_from(n: u128) -> Result<Self, Self::Error> {
+        Ulid::try_from(n).map(Self::from_ulid)
+    }
+}
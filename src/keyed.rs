@@ -0,0 +1,515 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{BuildHasher, Hash, RandomState},
+    sync::Mutex,
+    time::Duration,
+};
+
+#[cfg(any(feature = "rand", feature = "getrandom"))]
+use crate::STANDARD_ENTROPY_SOURCE;
+use crate::{EntropySourceHandle, GenerateError, RANDOM_BITS, Ulid, generator::Generator};
+
+/// Maximum number of shards, each with its own lock and entropy source.
+const MAX_SHARDS: usize = 16;
+
+/// A ULID generator with independent monotonic sequences per key.
+///
+/// While [`Ulid::new()`] generates ULIDs which are strictly monotonic across the whole process,
+/// a `KeyedGenerator` generates ULIDs which are strictly monotonic per key (e.g. per tenant or partition).
+/// Sequences of different keys are independent, and keys are spread over several locks,
+/// so generating ULIDs for different keys rarely contend with each other.
+///
+/// Each sequence has the same guarantees as the global generator: At least 10<sup>10</sup> ULIDs per
+/// millisecond without overflow, spilling over into the next millisecond if necessary.
+///
+/// Memory is bounded by the capacity given on creation. When the capacity is reached, the least
+/// recently used half of the keys is evicted. With [`with_idle_timeout()`](Self::with_idle_timeout),
+/// keys unused for a while are evicted, too. The sequence of an evicted key continues strictly
+/// monotonic when the key is used again, as the evicted state is folded into a high-water mark
+/// shared by all keys of the same lock.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::KeyedGenerator;
+///
+/// let generator = KeyedGenerator::new(10_000);
+///
+/// let a1 = generator.generate(&"tenant-a").unwrap();
+/// let b1 = generator.generate(&"tenant-b").unwrap();
+/// let a2 = generator.generate(&"tenant-a").unwrap();
+///
+/// assert!(a1 < a2);
+/// # assert!(b1 != a1);
+/// ```
+pub struct KeyedGenerator<K> {
+    hasher: RandomState,
+    shards: Box<[Mutex<Shard<K>>]>,
+}
+
+struct Shard<K> {
+    generator: Generator,
+    entries: HashMap<K, Entry>,
+    capacity: usize,
+    uses: u64,
+    evicted_ulid: u128,
+    idle_timeout: Option<u64>,
+    last_sweep: u64,
+}
+
+struct Entry {
+    last_ulid: u128,
+    last_used: u64,
+}
+
+impl<K: Hash + Eq + Clone> KeyedGenerator<K> {
+    /// Creates a `KeyedGenerator` for at most about `capacity` keys, using the standard entropy source.
+    ///
+    /// The actual bound is `capacity` rounded up to a multiple of the number of locks (at most 16).
+    #[cfg(any(feature = "rand", feature = "getrandom"))]
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self::with_entropy_source(capacity, || STANDARD_ENTROPY_SOURCE)
+    }
+
+    /// Creates a `KeyedGenerator` for at most about `capacity` keys, using custom entropy sources.
+    ///
+    /// As keys are spread over several locks, each with its own entropy source, the function
+    /// `source` is called once per lock to create the entropy sources.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::{KeyedGenerator, STANDARD_ENTROPY_SOURCE};
+    ///
+    /// let generator = KeyedGenerator::with_entropy_source(100, || STANDARD_ENTROPY_SOURCE);
+    ///
+    /// assert!(generator.generate(&42).is_some());
+    /// ```
+    #[must_use]
+    pub fn with_entropy_source(capacity: usize, mut source: impl FnMut() -> EntropySourceHandle) -> Self {
+        let capacity = capacity.max(1);
+        let shard_count = capacity.min(MAX_SHARDS);
+        let shard_capacity = capacity.div_ceil(shard_count);
+
+        let shards = (0..shard_count)
+            .map(|_| {
+                Mutex::new(Shard {
+                    generator: Generator::new(source()),
+                    entries: HashMap::new(),
+                    capacity: shard_capacity,
+                    uses: 0,
+                    evicted_ulid: 0,
+                    idle_timeout: None,
+                    last_sweep: 0,
+                })
+            })
+            .collect();
+
+        Self {
+            hasher: RandomState::new(),
+            shards,
+        }
+    }
+
+    /// Evicts keys which were not used for `timeout`, truncated to whole milliseconds.
+    ///
+    /// Idle keys are looked for while generating ULIDs, at most once per `timeout` and lock,
+    /// or on calling [`evict_idle()`](Self::evict_idle).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use mr_ulid::KeyedGenerator;
+    ///
+    /// let generator = KeyedGenerator::new(10_000).with_idle_timeout(Duration::from_secs(60));
+    ///
+    /// assert!(generator.generate(&"tenant-a").is_some());
+    /// ```
+    #[must_use]
+    pub fn with_idle_timeout(self, timeout: Duration) -> Self {
+        let timeout = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+        for shard in &self.shards {
+            if let Ok(mut shard) = shard.lock() {
+                shard.idle_timeout = Some(timeout);
+            }
+        }
+        self
+    }
+
+    /// Generates a new ULID, which is strictly greater than all ULIDs previously generated for `key`.
+    ///
+    /// Returns `None` in the case of problems with the entropy source.
+    /// Use [`KeyedGenerator::try_generate()`] to learn the cause.
+    #[must_use]
+    pub fn generate(&self, key: &K) -> Option<Ulid> {
+        self.try_generate(key).ok()
+    }
+
+    /// Generates a new ULID like [`KeyedGenerator::generate()`], returning the cause of a failure.
+    ///
+    /// # Errors
+    ///
+    /// Returns the cause if generating fails, like [`Ulid::try_generate()`],
+    /// or [`GenerateError::Poisoned`] if a thread panicked while generating a ULID of the same lock.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::KeyedGenerator;
+    ///
+    /// let generator = KeyedGenerator::new(10_000);
+    ///
+    /// let a1 = generator.try_generate(&"tenant-a")?;
+    /// let a2 = generator.try_generate(&"tenant-a")?;
+    /// assert!(a1 < a2);
+    /// # Ok::<(), mr_ulid::GenerateError>(())
+    /// ```
+    pub fn try_generate(&self, key: &K) -> Result<Ulid, GenerateError> {
+        let mut shard = self.shard(key).lock().map_err(|_| GenerateError::Poisoned)?;
+        shard.generate(key)
+    }
+
+    /// Returns the number of keys currently tracked.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().map_or(0, |shard| shard.entries.len()))
+            .sum()
+    }
+
+    /// Returns `true` if no keys are tracked.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Evicts the keys idle for longer than the timeout set by [`with_idle_timeout()`](Self::with_idle_timeout).
+    ///
+    /// Does nothing without an idle timeout. Idle keys are also evicted while generating ULIDs,
+    /// so calling this is only needed to release memory of locks rarely used.
+    pub fn evict_idle(&self) {
+        for shard in &self.shards {
+            if let Ok(mut shard) = shard.lock() {
+                if let Ok((now, _)) = shard.generator.timestamp() {
+                    shard.evict_idle(now);
+                }
+            }
+        }
+    }
+
+    fn shard(&self, key: &K) -> &Mutex<Shard<K>> {
+        #[allow(clippy::cast_possible_truncation)] // Truncation is fine for picking a shard
+        let index = self.hasher.hash_one(key) as usize % self.shards.len();
+        &self.shards[index]
+    }
+}
+
+impl<K: Hash + Eq + Clone> Shard<K> {
+    fn generate(&mut self, key: &K) -> Result<Ulid, GenerateError> {
+        self.uses += 1;
+
+        if !self.entries.contains_key(key) && self.entries.len() >= self.capacity {
+            self.evict();
+        }
+
+        let last_ulid = self.entries.get(key).map_or(self.evicted_ulid, |entry| entry.last_ulid);
+
+        self.generator.last_ulid = last_ulid;
+        let ulid = self.generator.next_ulid()?;
+
+        let entry = Entry {
+            last_ulid: ulid,
+            last_used: self.uses,
+        };
+        self.entries.insert(key.clone(), entry);
+
+        let now = timestamp(ulid);
+        if self
+            .idle_timeout
+            .is_some_and(|timeout| now >= self.last_sweep.saturating_add(timeout))
+        {
+            self.evict_idle(now);
+        }
+
+        Ulid::from_u128(ulid).ok_or(GenerateError::RandomnessOutOfRange)
+    }
+
+    /// Evicts the least recently used half of the entries, but at least one.
+    fn evict(&mut self) {
+        let mut uses: Vec<u64> = self.entries.values().map(|entry| entry.last_used).collect();
+        uses.sort_unstable();
+
+        // Uses are unique, so exactly the entries used before the threshold are evicted
+        let count = (uses.len() - uses.len() / 2).max(1);
+        let threshold = uses.get(count).copied().unwrap_or(u64::MAX);
+
+        self.evict_where(|entry| entry.last_used < threshold);
+    }
+
+    /// Evicts the entries which were not used within the idle timeout before `now`.
+    fn evict_idle(&mut self, now: u64) {
+        if let Some(timeout) = self.idle_timeout {
+            self.last_sweep = now;
+            self.evict_where(|entry| timestamp(entry.last_ulid).saturating_add(timeout) <= now);
+        }
+    }
+
+    /// Evicts the entries matching `evict`, keeping their ULIDs in the high-water mark.
+    fn evict_where(&mut self, mut evict: impl FnMut(&Entry) -> bool) {
+        let mut evicted_ulid = self.evicted_ulid;
+        self.entries.retain(|_, entry| {
+            let keep = !evict(entry);
+            if !keep {
+                evicted_ulid = evicted_ulid.max(entry.last_ulid);
+            }
+            keep
+        });
+        self.evicted_ulid = evicted_ulid;
+    }
+}
+
+/// Returns the timestamp of a ULID in milliseconds.
+#[allow(clippy::cast_possible_truncation)] // The timestamp has only 48 bits
+const fn timestamp(ulid: u128) -> u64 {
+    (ulid >> RANDOM_BITS) as u64
+}
+
+impl<K> fmt::Debug for KeyedGenerator<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("KeyedGenerator { ... }")
+    }
+}
+
+#[cfg(any(feature = "rand", feature = "getrandom"))]
+#[cfg(test)]
+mod tests {
+    use std::{
+        ops::RangeInclusive,
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+    };
+
+    use super::*;
+    use crate::{EntropySource, RANDOM_BITS};
+
+    /// An entropy source with a clock standing still and worst-case randomness.
+    struct FrozenSource;
+    impl EntropySource for FrozenSource {
+        fn timestamp(&mut self) -> Option<u64> {
+            Some(1000)
+        }
+        fn random(&mut self, range: RangeInclusive<u128>) -> Option<u128> {
+            Some(*range.end())
+        }
+    }
+
+    /// An entropy source with a clock set by the test.
+    struct ManualClockSource(Arc<AtomicU64>);
+    impl EntropySource for ManualClockSource {
+        fn timestamp(&mut self) -> Option<u64> {
+            Some(self.0.load(Ordering::Relaxed))
+        }
+        fn random(&mut self, range: RangeInclusive<u128>) -> Option<u128> {
+            Some(*range.start())
+        }
+    }
+
+    #[test]
+    fn test_monotonic_per_key() {
+        let generator = KeyedGenerator::new(100);
+
+        let mut last = HashMap::new();
+
+        for i in 0..1000 {
+            let key = i % 7;
+            let ulid = generator.generate(&key).unwrap();
+            if let Some(&previous) = last.get(&key) {
+                assert!(ulid > previous);
+            }
+            last.insert(key, ulid);
+        }
+
+        assert_eq!(generator.len(), 7);
+    }
+
+    #[test]
+    fn test_independent_sequences() {
+        // Room for two keys per lock, so none is evicted
+        let generator = KeyedGenerator::with_entropy_source(32, || EntropySourceHandle::new(FrozenSource));
+
+        let a1 = generator.generate(&"a").unwrap();
+        let b1 = generator.generate(&"b").unwrap();
+        let a2 = generator.generate(&"a").unwrap();
+
+        // With a global sequence, `b1` would be between `a1` and `a2`
+        assert_eq!(a1, b1);
+        assert_eq!(a2.to_u128(), a1.to_u128() + 1);
+    }
+
+    #[test]
+    fn test_spill_over() {
+        let generator = KeyedGenerator::with_entropy_source(1, || EntropySourceHandle::new(FrozenSource));
+
+        let mut shard = generator.shards[0].lock().unwrap();
+        let entry = Entry {
+            last_ulid: (1000 << RANDOM_BITS) | ((1 << RANDOM_BITS) - 1),
+            last_used: 0,
+        };
+        shard.entries.insert("a", entry);
+
+        let ulid = shard.generate(&"a").unwrap();
+        drop(shard);
+
+        assert_eq!(ulid.timestamp(), 1001);
+        assert_eq!(ulid.randomness(), 0);
+    }
+
+    #[test]
+    fn test_eviction() {
+        let generator = KeyedGenerator::with_entropy_source(32, || EntropySourceHandle::new(FrozenSource));
+
+        let mut first = HashMap::new();
+        for key in 0..100 {
+            first.insert(key, generator.generate(&key).unwrap());
+            assert!(generator.len() <= 32);
+        }
+
+        // Monotonic also for evicted keys
+        for key in 0..100 {
+            assert!(generator.generate(&key).unwrap() > first[&key]);
+        }
+
+        assert!(!generator.is_empty());
+    }
+
+    #[test]
+    fn test_eviction_small_capacity() {
+        for capacity in 1..=17 {
+            let generator = KeyedGenerator::with_entropy_source(capacity, || EntropySourceHandle::new(FrozenSource));
+            let bound = generator.shards.len() * capacity.div_ceil(generator.shards.len());
+
+            for key in 0..100 {
+                assert!(generator.generate(&key).is_some());
+                assert!(generator.len() <= bound, "capacity: {capacity}");
+            }
+
+            if capacity <= 16 {
+                assert_eq!(bound, capacity);
+            }
+        }
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let generator = KeyedGenerator::with_entropy_source(4, || EntropySourceHandle::new(FrozenSource));
+        let mut shard = generator.shards[0].lock().unwrap();
+        shard.capacity = 4;
+
+        for key in 0..4 {
+            assert!(shard.generate(&key).is_ok());
+        }
+        assert!(shard.generate(&0).is_ok());
+
+        // Keys 1 and 2 were used least recently
+        assert!(shard.generate(&4).is_ok());
+        let mut keys: Vec<_> = shard.entries.keys().copied().collect();
+        drop(shard);
+        keys.sort_unstable();
+        assert_eq!(keys, [0, 3, 4]);
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let clock = Arc::new(AtomicU64::new(1000));
+        let generator = KeyedGenerator::with_entropy_source(1000, || {
+            EntropySourceHandle::new(ManualClockSource(Arc::clone(&clock)))
+        })
+        .with_idle_timeout(Duration::from_secs(1));
+
+        let mut first = Vec::new();
+        for key in 0..100 {
+            first.push(generator.generate(&key).unwrap());
+        }
+        assert_eq!(generator.len(), 100);
+
+        clock.store(1999, Ordering::Relaxed);
+        generator.evict_idle();
+        assert_eq!(generator.len(), 100);
+
+        clock.store(2000, Ordering::Relaxed);
+        assert!(generator.generate(&100).is_some());
+        generator.evict_idle();
+        assert_eq!(generator.len(), 1);
+
+        // Monotonic also for evicted keys
+        clock.store(1000, Ordering::Relaxed);
+        for (key, first) in first.into_iter().enumerate() {
+            assert!(generator.generate(&key).unwrap() > first);
+        }
+    }
+
+    #[test]
+    fn test_idle_timeout_while_generating() {
+        let clock = Arc::new(AtomicU64::new(1000));
+        let generator =
+            KeyedGenerator::with_entropy_source(1, || EntropySourceHandle::new(ManualClockSource(Arc::clone(&clock))))
+                .with_idle_timeout(Duration::from_millis(10));
+
+        assert!(generator.generate(&"a").is_some());
+        clock.store(1010, Ordering::Relaxed);
+        assert!(generator.generate(&"a").is_some());
+        assert_eq!(generator.len(), 1);
+
+        let mut shard = generator.shards[0].lock().unwrap();
+        shard.capacity = 2;
+        assert!(shard.generate(&"b").is_ok());
+        assert_eq!(shard.entries.len(), 2);
+        drop(shard);
+
+        // Sweeping on generating "b" evicts the idle "a"
+        clock.store(1020, Ordering::Relaxed);
+        assert!(generator.generate(&"b").is_some());
+        assert_eq!(generator.len(), 1);
+    }
+
+    #[test]
+    fn test_try_generate_errors() {
+        struct NoClockSource;
+        impl EntropySource for NoClockSource {
+            fn timestamp(&mut self) -> Option<u64> {
+                None
+            }
+            fn random(&mut self, range: RangeInclusive<u128>) -> Option<u128> {
+                Some(*range.start())
+            }
+        }
+
+        let generator = KeyedGenerator::with_entropy_source(1, || EntropySourceHandle::new(NoClockSource));
+        assert_eq!(generator.try_generate(&"a"), Err(GenerateError::TimestampUnavailable));
+        assert_eq!(generator.generate(&"a"), None);
+
+        // A single lock, poisoned by a panicking thread
+        let generator = KeyedGenerator::new(1);
+        std::thread::scope(|scope| {
+            let poison = scope.spawn(|| {
+                let _shard = generator.shards[0].lock().unwrap();
+                panic!("poison the lock");
+            });
+            assert!(poison.join().is_err());
+        });
+        assert_eq!(generator.try_generate(&"a"), Err(GenerateError::Poisoned));
+    }
+
+    #[test]
+    fn test_send_sync() {
+        const fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<KeyedGenerator<String>>();
+    }
+}
@@ -0,0 +1,170 @@
+use core::num::NonZero;
+
+use crate::{GenerateError, Ulid, generator};
+
+/// A lease of consecutive ULIDs, to generate ULIDs without any shared state.
+///
+/// A `UlidLease` reserves a contiguous range of the random component within a single millisecond
+/// from the global generator. All ULIDs of the range are taken from the lease with [`Iterator::next()`],
+/// which just increments a counter: no locking, no entropy source, no shared state.
+/// This way, a coordinator can hand out leases to workers, and the workers mint ULIDs locally.
+///
+/// ULIDs of a lease are unique by construction: The global generator never generates a ULID within a leased
+/// range, and continues after the range. ULIDs taken from a single lease are strictly monotonic,
+/// but ULIDs of different leases are only ordered by the order of their acquisition.
+///
+/// An exhausted lease returns `None` (and [`UlidLease::is_exhausted()`] returns `true`).
+/// It can be renewed with [`UlidLease::renew()`], which acquires a new range of the same size.
+///
+/// `UlidLease` deliberately does not implement `Clone`, as clones would hand out the same ULIDs.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::UlidLease;
+///
+/// let mut lease = UlidLease::acquire(1000)?;
+///
+/// let u1 = lease.next().unwrap();
+/// let u2 = lease.next().unwrap();
+///
+/// assert_eq!(u2.to_u128(), u1.to_u128() + 1);
+///
+/// while lease.next().is_some() {}
+/// assert!(lease.is_exhausted());
+///
+/// lease.renew()?;
+/// assert!(lease.next().unwrap() > u2);
+/// # Ok::<(), mr_ulid::GenerateError>(())
+/// ```
+#[derive(Debug)]
+pub struct UlidLease {
+    next: u128,
+    remaining: u64,
+    count: u64,
+}
+
+impl UlidLease {
+    /// Acquires a lease of `count` consecutive ULIDs from the global generator.
+    ///
+    /// The range of a lease never extends beyond the millisecond of its first ULID. As the generator
+    /// leaves room for at least 10<sup>10</sup> ULIDs per millisecond, leases of up to 10<sup>10</sup> ULIDs
+    /// normally get the full range. Larger leases, or leases acquired after the generator spilled over
+    /// into the next millisecond, may get fewer ULIDs (see [`UlidLease::remaining()`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GenerateError::EmptyLease`] if `count` is zero, and the cause if the generator fails,
+    /// like [`Ulid::try_generate()`].
+    pub fn acquire(count: u64) -> Result<Self, GenerateError> {
+        let count_non_zero = NonZero::new(count).ok_or(GenerateError::EmptyLease)?;
+        let (next, end) = generator::generate_range(count_non_zero)?;
+        Ok(Self::from_range(next, end, count))
+    }
+
+    /// Creates a lease of the ULIDs `next..=end`, which are at most `count`.
+    const fn from_range(next: u128, end: u128, count: u64) -> Self {
+        // The range is never larger than the `u64` requested
+        #[allow(clippy::cast_possible_truncation)]
+        let remaining = (end - next + 1) as u64;
+        Self { next, remaining, count }
+    }
+
+    /// Replaces the lease with a new lease of the same size as initially requested.
+    ///
+    /// Remaining ULIDs of the lease are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the cause if the generator fails, like [`Ulid::try_generate()`].
+    /// The lease is left unchanged then.
+    pub fn renew(&mut self) -> Result<(), GenerateError> {
+        *self = Self::acquire(self.count)?;
+        Ok(())
+    }
+
+    /// Returns the number of ULIDs remaining in the lease.
+    #[must_use]
+    pub const fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Returns `true` if all ULIDs of the lease have been taken.
+    #[must_use]
+    pub const fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+impl Iterator for UlidLease {
+    type Item = Ulid;
+
+    fn next(&mut self) -> Option<Ulid> {
+        if self.is_exhausted() {
+            return None;
+        }
+        let ulid = self.next;
+        self.remaining -= 1;
+
+        // A lease may end at the largest possible ULID, which has no successor
+        match ulid.checked_add(1) {
+            Some(next) => self.next = next,
+            None => self.remaining = 0,
+        }
+
+        Ulid::from_u128(ulid)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.remaining()).ok();
+        (remaining.unwrap_or(usize::MAX), remaining)
+    }
+}
+
+#[cfg(any(feature = "rand", feature = "getrandom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lease() {
+        let mut lease = UlidLease::acquire(100).unwrap();
+        assert_eq!(lease.remaining(), 100);
+        assert_eq!(lease.size_hint(), (100, Some(100)));
+
+        let ulids: Vec<_> = lease.by_ref().collect();
+        assert_eq!(ulids.len(), 100);
+        assert!(ulids.windows(2).all(|w| w[1].to_u128() == w[0].to_u128() + 1));
+        assert!(ulids.iter().all(|u| u.timestamp() == ulids[0].timestamp()));
+
+        assert!(lease.is_exhausted());
+        assert_eq!(lease.remaining(), 0);
+        assert_eq!(lease.next(), None);
+
+        // The global generator continues after the lease
+        assert!(Ulid::new() > ulids[99]);
+
+        lease.renew().unwrap();
+        assert_eq!(lease.remaining(), 100);
+        assert!(lease.next().unwrap() > ulids[99]);
+    }
+
+    #[test]
+    fn test_lease_ending_at_max() {
+        let mut lease = UlidLease::from_range(u128::MAX - 2, u128::MAX, 3);
+        assert_eq!(lease.remaining(), 3);
+
+        let ulids: Vec<_> = lease.by_ref().map(Ulid::to_u128).collect();
+        assert_eq!(ulids, [u128::MAX - 2, u128::MAX - 1, u128::MAX]);
+
+        assert!(lease.is_exhausted());
+        assert_eq!(lease.remaining(), 0);
+        assert_eq!(lease.next(), None);
+        assert_eq!(lease.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_empty_lease() {
+        assert_eq!(UlidLease::acquire(0).unwrap_err(), GenerateError::EmptyLease);
+    }
+}
@@ -34,6 +34,9 @@
 //!
 //! ```
 //!
+//! Width, fill and alignment are honored (e.g. `{u:>30}`), and the alternate flag `{u:#}` prints lowercase,
+//! as does [`Ulid::display_lower()`]. The 128-bit value can be printed with `{u:x}`, `{u:X}`, and `{u:b}`.
+//!
 //! ## Parsing ULIDs from Strings:
 //!
 //! ULIDs implements the [`std::str::FromStr`] trait and can be parsed with [`str::parse()`] method:
@@ -53,6 +56,17 @@
 //! # Ok(()) }
 //! ```
 //!
+//! Parsing accepts lowercase letters and the aliases `I`, `L`, `O` for `1`, `1`, `0`.
+//! To accept only the canonical form, or to accept copy-pasted ULIDs with whitespace, quotes,
+//! hyphens, or a `urn:ulid:` prefix, use [`Ulid::parse_with()`] with [`ParseOptions`].
+//!
+//! ## Batches of ULIDs
+//!
+//! For columns of many ULIDs, [`encode_many()`] and [`decode_many()`] convert between slices of ULIDs
+//! and fixed-stride text of 26 bytes per ULID, without allocation. [`encode_separated()`] and
+//! [`decode_separated()`] handle newline- or comma-separated text. Errors report the index of the first
+//! invalid entry.
+//!
 //! ## Serializing and Deserializing using `Serde` (JSON)
 //!
 //! For serializing/deserializing the feature flag `serde` needs to be enabled:
@@ -111,6 +125,35 @@
 //! per second, any overflowing random part is projected into the next millisecond.
 //! There, the full range of 2<sup>80</sup> (ca. 10<sup>24</sup>) is available.
 //!
+//! ## Independent Sequences per Key
+//!
+//! ULIDs generated with [`Ulid::new()`] are strictly monotonic across the whole process.
+//! If strict monotonicity is only needed per tenant or partition, a [`KeyedGenerator`]
+//! keeps independent sequences per key, without serializing all keys through one lock.
+//!
+//! ## Leasing Ranges of ULIDs
+//!
+//! For very high throughput, a [`UlidLease`] reserves a contiguous range of ULIDs within a millisecond.
+//! ULIDs are then taken from the lease without locking or shared state, e.g. by worker threads,
+//! while uniqueness is guaranteed by construction.
+//!
+//! ## Recording and Replaying Entropy
+//!
+//! A [`RecordingSource`] wraps any entropy source and logs every timestamp and random number it returns.
+//! Feeding the log into a [`ReplaySource`] reproduces exactly the same sequence of ULIDs,
+//! which helps to reproduce failures of tests or incidents deterministically.
+//!
+//! ## Sub-Millisecond Precision
+//!
+//! Optionally, generated ULIDs can carry a finer timestamp, similar to "method 3" of `UUIDv7`:
+//! After calling [`set_sub_millisecond_precision(true)`](set_sub_millisecond_precision), the top 12 bits of
+//! the random component store the fraction of the millisecond (in units of 1/4096 ms, about 244 ns).
+//! The remaining 68 bits are random. [`Ulid::timestamp_nanos()`] recovers the finer timestamp.
+//!
+//! Such ULIDs are fully compatible with plain ULID readers: The 48-bit millisecond timestamp is unchanged,
+//! and since the fraction precedes the random bits, lexicographical and numerical order still match
+//! the order of generation, even with a finer resolution. Plain readers just see 12 bits less randomness.
+//!
 //! ## ULID Types
 //!
 //! - [`Ulid`]: This is the preferred type for most use cases and represents a ULID that can never be zero.
@@ -123,33 +166,134 @@
 //! ## Feature Flags
 //!
 //! - **`rand`**: Utilizes the `rand` crate as the source for random numbers, enabled by default.
+//! - **`getrandom`**: Utilizes the operating system's random number generator directly via the `getrandom` crate,
+//!   optional. A lightweight alternative to `rand`, used only if `rand` is disabled.
 //! - **`serde`**: Provides support for serialization and deserialization via `Serde`, optional.
+//! - **`uuid`**: Provides conversions from and to `uuid::Uuid`, optional.
+//! - **`derive`**: Provides `#[derive(UlidNewtype)]` for newtypes like `struct OrderId(Ulid)`, optional.
+//! - **`std`**: Enables everything depending on the standard library, enabled by default.
+//! - **`alloc`**: Enables the APIs returning `String`s without requiring `std`, optional (implied by `std`).
+//! - **`critical-section`**: Enables ULID generation without `std`, using the `critical-section` crate for locking, optional.
+//! - **`shared-state`**: Enables strict monotonicity across processes on the same host via a shared
+//!   state file (see `set_shared_state_file()`), optional.
+//!
+//! ## `no_std` Support
+//!
+//! Without the `std` feature, this crate is `no_std`. The ULID types, parsing, formatting,
+//! and [`Ulid::from_parts()`] are always available. Generating ULIDs additionally requires
+//! the `critical-section` feature and a custom [`EntropySource`] set with [`set_entropy_source()`],
+//! because there is neither a system clock nor an operating system random number generator:
+//!
+//! ```toml
+//! [dependencies]
+//! mr-ulid = { version = "3", default-features = false, features = ["critical-section"] }
+//! ```
 //!
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 mod base32;
+mod batch;
+mod compact;
 mod error;
+#[cfg(any(feature = "std", feature = "critical-section"))]
 mod generator;
+#[cfg(any(feature = "std", feature = "critical-section"))]
+mod health;
+mod hex;
+mod human;
+mod id;
+#[cfg(feature = "std")]
+mod keyed;
+#[cfg(any(feature = "std", feature = "critical-section"))]
+mod lease;
 mod nonzero;
+mod parse;
+mod prefixed;
+#[cfg(feature = "std")]
+mod replay;
 #[cfg(feature = "serde")]
 mod serde;
+#[cfg(feature = "shared-state")]
+mod shared;
+mod string;
+mod typeid;
 mod util;
+#[cfg(feature = "uuid")]
+mod uuid;
 mod zeroable;
 
-use std::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::ToString as _};
 
-pub use error::Error;
-#[cfg(feature = "rand")]
+pub use batch::{decode_many, encode_many};
+#[cfg(feature = "alloc")]
+pub use batch::{decode_separated, encode_separated};
+#[cfg(any(feature = "std", feature = "critical-section"))]
+pub use error::GenerateError;
+pub use error::{BatchError, Error, ErrorWithInput, PrefixedUlidError, TypeIdError, Typo};
+#[cfg(feature = "getrandom")]
+pub use generator::OS_ENTROPY_SOURCE;
+#[cfg(any(feature = "rand", feature = "getrandom"))]
 pub use generator::STANDARD_ENTROPY_SOURCE;
-pub use generator::{EntropySource, EntropySourceHandle, NO_ENTROPY_SOURCE, set_entropy_source};
+#[cfg(feature = "shared-state")]
+pub use generator::set_shared_state_file;
+#[cfg(any(feature = "std", feature = "critical-section"))]
+pub use generator::{
+    EntropySource, EntropySourceHandle, NO_ENTROPY_SOURCE, health_test_failure, reset_high_water_mark,
+    set_entropy_source, set_health_tests, set_max_ahead_of_clock, set_sub_millisecond_precision, used_entropy_source,
+};
+#[cfg(any(feature = "std", feature = "critical-section"))]
+pub use health::HealthTestFailure;
+pub use human::{TypoCandidates, typo_candidates};
+pub use id::Id;
+#[cfg(feature = "std")]
+pub use keyed::KeyedGenerator;
+#[cfg(any(feature = "std", feature = "critical-section"))]
+pub use lease::UlidLease;
 pub use nonzero::Ulid;
+pub use parse::ParseOptions;
+pub use prefixed::{PrefixedUlid, UlidPrefix};
+#[cfg(feature = "std")]
+pub use replay::{RecordingSource, ReplaySource};
+pub use string::{DisplayLower, UlidString};
+pub use typeid::TypeId;
 pub use zeroable::ZeroableUlid;
 
+#[cfg(feature = "derive")]
+pub use mr_ulid_derive::UlidNewtype;
+
+/// Support for the code generated by `#[derive(UlidNewtype)]`, not part of the public API.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::prefixed::{fmt_prefixed, is_valid_prefix, parse_prefixed};
+    #[cfg(feature = "serde")]
+    pub use crate::serde::deserialize_from_str;
+    #[cfg(feature = "serde")]
+    pub use serde;
+}
+
+#[cfg(any(feature = "std", feature = "critical-section"))]
 const RESERVED: u128 = 10_000_000_000;
 
 const RANDOM_BITS: u32 = 80;
 const RANDOM_MASK: u128 = (1 << RANDOM_BITS) - 1;
+#[cfg(any(feature = "std", feature = "critical-section"))]
 const RANDOM_GEN_MAX: u128 = RANDOM_MASK - RESERVED;
 
+const SUB_MILLIS_BITS: u32 = 12;
+const SUB_MILLIS_RANDOM_BITS: u32 = RANDOM_BITS - SUB_MILLIS_BITS;
+#[cfg(any(feature = "std", feature = "critical-section"))]
+const SUB_MILLIS_RANDOM_GEN_MAX: u128 = (1 << SUB_MILLIS_RANDOM_BITS) - 1 - RESERVED;
+#[cfg(any(feature = "std", feature = "critical-section"))]
+const SUB_MILLIS_TICK_MASK: u128 = !((1 << SUB_MILLIS_RANDOM_BITS) - 1);
+
+const NANOS_PER_MILLI: u128 = 1_000_000;
+
 const TIMESTAMP_BITS: u32 = 48;
 const TIMESTAMP_MAX: u64 = (1 << TIMESTAMP_BITS) - 1;
 const TIMESTAMP_MASK: u128 = ((1 << TIMESTAMP_BITS) - 1) << RANDOM_BITS;
@@ -178,6 +322,7 @@ const TIMESTAMP_MASK: u128 = ((1 << TIMESTAMP_BITS) - 1) << RANDOM_BITS;
 /// assert_eq!(mr_ulid::canonicalize(s), Ok("011XJAZTHSFJZT7WD6J81R92VN".into()));
 /// ```
 ///
+#[cfg(feature = "alloc")]
 pub fn canonicalize(ulid: &str) -> Result<Cow<'_, str>, Error> {
     let mut buffer = *util::as_array(ulid.as_bytes())?;
     let cleaned = base32::canonicalize(&mut buffer)?;
@@ -188,6 +333,39 @@ pub fn canonicalize(ulid: &str) -> Result<Cow<'_, str>, Error> {
         Ok(cleaned.to_string().into())
     }
 }
+
+/// Canonicalizes a ULID string like [`canonicalize()`], with options controlling how strict the parser is.
+///
+/// With lenient options, the canonical form has whitespace, quotes, prefix and hyphens removed.
+/// A borrowed version of the input string is returned if it is already in canonical form.
+///
+/// # Errors
+///
+/// Returns an error if the string is not a valid ULID according to `options`.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::ParseOptions;
+///
+/// let s = "urn:ulid:01jb5c84zb-m8qvbe5qr-zw6hy89";
+/// assert_eq!(
+///     mr_ulid::canonicalize_with(s, ParseOptions::LENIENT),
+///     Ok("01JB5C84ZBM8QVBE5QRZW6HY89".into())
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+pub fn canonicalize_with(ulid: &str, options: ParseOptions) -> Result<Cow<'_, str>, Error> {
+    let mut buffer = [0; 26];
+    let canonical = base32::encode(parse::parse(ulid, options)?, &mut buffer);
+
+    if canonical == ulid {
+        Ok(ulid.into())
+    } else {
+        Ok(canonical.to_string().into())
+    }
+}
+
 /// Checks a ULID string for validity.
 ///
 /// To be valid, a ULID must have the correct length (26) and contain only valid characters,
@@ -202,6 +380,8 @@ pub fn canonicalize(ulid: &str) -> Result<Cow<'_, str>, Error> {
 /// # Example
 ///
 /// ```
+/// use mr_ulid::Error;
+///
 // cspell:disable-next-line
 /// assert!(mr_ulid::validate("olixjazthsfjzt7wd6j8ir92vn").is_ok());
 // cspell:disable-next-line
@@ -209,16 +389,37 @@ pub fn canonicalize(ulid: &str) -> Result<Cow<'_, str>, Error> {
 ///
 /// assert!(mr_ulid::validate("00000000000000000000000000").is_ok());
 /// assert!(mr_ulid::validate("7FFFFFFFFFFFFFFFFFFFFFFFFF").is_ok());
-/// assert_eq!(mr_ulid::validate("80000000000000000000000000"), Err(mr_ulid::Error::InvalidChar));
+/// assert_eq!(mr_ulid::validate("80000000000000000000000000"), Err(Error::Overflow { offset: 0, byte: b'8' }));
 ///
-/// assert_eq!(mr_ulid::validate("0000000000000000000000u89$"), Err(mr_ulid::Error::InvalidChar));
-/// assert_eq!(mr_ulid::validate("xxxxxxxxxxxxxxxxxxxxxx"), Err(mr_ulid::Error::TooShort));
-/// assert_eq!(mr_ulid::validate("xxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"), Err(mr_ulid::Error::TooLong));
+/// assert_eq!(mr_ulid::validate("0000000000000000000000u89$"), Err(Error::InvalidChar { offset: 22, byte: b'u' }));
+/// assert_eq!(mr_ulid::validate("xxxxxxxxxxxxxxxxxxxxxx"), Err(Error::TooShort { expected: 26, actual: 22 }));
+/// assert_eq!(mr_ulid::validate("xxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"), Err(Error::TooLong { expected: 26, actual: 30 }));
 /// ```
 pub fn validate(ulid: &str) -> Result<(), Error> {
     let buffer = util::as_array(ulid.as_bytes())?;
     base32::validate(buffer)
 }
 
-#[cfg(test)]
+/// Checks a ULID string for validity like [`validate()`], with options controlling how strict the parser is.
+///
+/// # Errors
+///
+/// If the ULID string is not valid according to `options`, an appropriate error is returned.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::{Error, ParseOptions};
+///
+/// assert!(mr_ulid::validate_with("'01JB5C84ZBM8QVBE5QRZW6HY89'", ParseOptions::LENIENT).is_ok());
+/// assert_eq!(
+///     mr_ulid::validate_with("01JB5C84ZBM8QVBE5QRZW6HYO9", ParseOptions::STRICT),
+///     Err(Error::NonCanonicalChar { offset: 24, byte: b'O' })
+/// );
+/// ```
+pub fn validate_with(ulid: &str, options: ParseOptions) -> Result<(), Error> {
+    parse::parse(ulid, options).map(|_| ())
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests;
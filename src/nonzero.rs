@@ -1,11 +1,15 @@
-use std::{
-    fmt,
-    num::NonZero,
-    str::FromStr,
-    time::{Duration, SystemTime},
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::{fmt, num::NonZero, str::FromStr};
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    DisplayLower, Error, ParseOptions, RANDOM_BITS, RANDOM_MASK, UlidString, ZeroableUlid, base32, compact, hex, human,
+    parse, util,
 };
-
-use crate::{Error, RANDOM_BITS, RANDOM_MASK, ZeroableUlid, base32, generator, util};
+#[cfg(any(feature = "std", feature = "critical-section"))]
+use crate::{GenerateError, generator};
 
 /// A ULID which never is zero.
 ///
@@ -94,9 +98,10 @@ impl Ulid {
     ///
     /// assert!((t1 < t2) || (t1 == t2 && r1 < r2));
     /// ```
+    #[cfg(any(feature = "std", feature = "critical-section"))]
     #[must_use]
     pub fn new() -> Self {
-        Self(NonZero::new(generator::generate().unwrap()).unwrap())
+        Self::try_generate().unwrap_or_else(|error| panic!("failed to generate ULID: {error}"))
     }
 
     /// Returns the timestamp part of a `Ulid`.
@@ -118,6 +123,27 @@ impl Ulid {
         (self.0.get() >> RANDOM_BITS) as u64
     }
 
+    /// Returns the timestamp of a `Ulid` in nanoseconds since the Unix epoch.
+    ///
+    /// This is only meaningful for ULIDs generated with sub-millisecond precision
+    /// (see [`set_sub_millisecond_precision()`](crate::set_sub_millisecond_precision)), where the top 12 bits
+    /// of the randomness store the fraction of the millisecond. The result has a resolution of about 244 ns.
+    /// For other ULIDs, the sub-millisecond part is just random.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_parts(1, 0x800 << 68).unwrap();
+    ///
+    /// assert_eq!(u.timestamp_nanos(), 1_500_000);
+    /// ```
+    #[must_use]
+    pub const fn timestamp_nanos(self) -> u128 {
+        util::timestamp_nanos(self.0.get())
+    }
+
     /// Returns the random part of a `Ulid`.
     ///
     /// The randomness of a `ULID` is limited to 80 bits.
@@ -157,6 +183,7 @@ impl Ulid {
     ///
     /// assert!(u.datetime() <= SystemTime::now());
     /// ```
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn datetime(self) -> SystemTime {
         SystemTime::UNIX_EPOCH + Duration::from_millis(self.timestamp())
@@ -319,6 +346,378 @@ impl Ulid {
         self.0.get()
     }
 
+    /// Converts a `Ulid` into the hyphenated 8-4-4-4-12 hex form of UUIDs, in lowercase.
+    ///
+    /// The UUID has the same bytes as [`Ulid::to_bytes()`], so the ULID can be stored in a UUID column
+    /// and read back with [`Ulid::from_uuid_str()`]. The UUID is no valid RFC 9562 UUID, as the
+    /// version and variant bits are part of the ULID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    ///
+    /// assert_eq!(u.to_uuid_string(), "0192fa64-e41e-5b4a-45e0-3fd8f28b7522");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_uuid_string(self) -> String {
+        let mut buffer = [0; 36];
+        String::from(hex::encode(self.0.get(), &mut buffer))
+    }
+
+    /// Parses a `Ulid` from the hyphenated 8-4-4-4-12 hex form of UUIDs.
+    ///
+    /// Hex digits may be upper- or lowercase. Braces, a `urn:uuid:` prefix, or the simple form without
+    /// hyphens are not accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't a hyphenated UUID.
+    /// Returns [`Error::InvalidZero`] for the nil UUID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_uuid_str("0192FA64-E41E-5B4A-45E0-3FD8F28B7522")?;
+    ///
+    /// assert_eq!(u.to_u128(), 0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    /// # Ok::<(), mr_ulid::Error>(())
+    /// ```
+    pub fn from_uuid_str(s: &str) -> Result<Self, Error> {
+        Self::from_u128(hex::decode(s.as_bytes())?).ok_or(Error::InvalidZero)
+    }
+
+    /// Converts a `Ulid` into 22 characters of Base58, using Bitcoin's alphabet.
+    ///
+    /// The alphabet omits the look-alikes `0`, `O`, `I` and `l`, and is in ASCII order. The string has
+    /// a fixed length with leading `1`s (the zero digit), so strings sort like the ULIDs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.to_base58_string(), "1CGu1BjU9Rr9h2ZVm6P3oo");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_base58_string(self) -> String {
+        let mut buffer = [0; compact::LEN];
+        String::from(compact::encode(&compact::BASE58, self.0.get(), &mut buffer))
+    }
+
+    /// Parses a `Ulid` from 22 characters of Base58, as created by [`Ulid::to_base58_string()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't 22 characters of Base58,
+    /// or [`Error::ValueTooLarge`] if it represents a value larger than 128 bits.
+    /// Returns [`Error::InvalidZero`] for the value zero.
+    pub fn from_base58_str(s: &str) -> Result<Self, Error> {
+        Self::from_u128(compact::decode(&compact::BASE58, s.as_bytes())?).ok_or(Error::InvalidZero)
+    }
+
+    /// Converts a `Ulid` into 22 characters of Base62, using digits, uppercase and lowercase letters.
+    ///
+    /// The alphabet is in ASCII order, and the string has a fixed length with leading `0`s,
+    /// so strings sort like the ULIDs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.to_base62_string(), "02yA0piThDHxaCD6tFIMHi");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_base62_string(self) -> String {
+        let mut buffer = [0; compact::LEN];
+        String::from(compact::encode(&compact::BASE62, self.0.get(), &mut buffer))
+    }
+
+    /// Parses a `Ulid` from 22 characters of Base62, as created by [`Ulid::to_base62_string()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't 22 characters of Base62,
+    /// or [`Error::ValueTooLarge`] if it represents a value larger than 128 bits.
+    /// Returns [`Error::InvalidZero`] for the value zero.
+    pub fn from_base62_str(s: &str) -> Result<Self, Error> {
+        Self::from_u128(compact::decode(&compact::BASE62, s.as_bytes())?).ok_or(Error::InvalidZero)
+    }
+
+    /// Converts a `Ulid` into 22 characters of unpadded base64url (RFC 4648), encoding the bytes of [`Ulid::to_bytes()`].
+    ///
+    /// Other base64url decoders read the string as the 16 bytes of the ULID. Unlike Base58 and Base62,
+    /// the alphabet is not in ASCII order, so the strings don't sort like the ULIDs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.to_base64url_string(), "AZL6ZOQeW0pF4D_Y8ot1Ig");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_base64url_string(self) -> String {
+        let mut buffer = [0; compact::LEN];
+        String::from(compact::encode_base64url(self.0.get(), &mut buffer))
+    }
+
+    /// Parses a `Ulid` from 22 characters of unpadded base64url (RFC 4648), as created by [`Ulid::to_base64url_string()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't 22 characters of base64url, or if the unused low 4 bits of the last
+    /// character aren't zero.
+    /// Returns [`Error::InvalidZero`] for the value zero.
+    pub fn from_base64url_str(s: &str) -> Result<Self, Error> {
+        Self::from_u128(compact::decode_base64url(s.as_bytes())?).ok_or(Error::InvalidZero)
+    }
+
+    /// Converts a `Ulid` into a form for reading aloud and typing by hand: Groups of 5 characters,
+    /// separated by hyphens, followed by a Crockford check symbol.
+    ///
+    /// The check symbol is the value modulo 37, so every single mistyped character is detected
+    /// by [`Ulid::from_human_str()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.to_human_string(), "01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2D");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_human_string(self) -> String {
+        let mut buffer = [0; human::LEN];
+        String::from(human::encode(self.0.get(), &mut buffer))
+    }
+
+    /// Parses a `Ulid` from the form created by [`Ulid::to_human_string()`], verifying the check symbol.
+    ///
+    /// Hyphens are ignored anywhere, and letters are case-insensitive.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't a ULID followed by a check symbol.
+    /// Returns [`Error::ChecksumMismatch`] if the check symbol doesn't match, with the likely typo
+    /// if a single correction between easily confused characters fixes it; see [`typo_candidates()`](crate::typo_candidates)
+    /// for all such corrections.
+    /// Returns [`Error::InvalidZero`] for the value zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::{Error, Ulid};
+    ///
+    // cspell:disable-next-line
+    /// let u = Ulid::from_human_str("01jbx-69s0y-bd54b-r1zv3-s8px9-2d")?;
+    /// assert_eq!(u.to_u128(), 0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    ///
+    // cspell:disable-next-line
+    /// let error = Ulid::from_human_str("01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2E").unwrap_err();
+    /// assert!(matches!(error, Error::ChecksumMismatch { .. }));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn from_human_str(s: &str) -> Result<Self, Error> {
+        Self::from_u128(human::decode(s)?).ok_or(Error::InvalidZero)
+    }
+
+    /// Converts a `Ulid` into a UUID v7 (as `u128`, like [`Ulid::to_u128()`]).
+    ///
+    /// The 48-bit timestamp is preserved exactly. A UUID v7 has only 74 random bits, as it reserves 6 bits
+    /// for version and variant. The top 12 random bits of the ULID become `rand_a` (which holds the fraction
+    /// of the millisecond with [`set_sub_millisecond_precision()`](crate::set_sub_millisecond_precision),
+    /// like RFC 9562 method 3), and the low 62 random bits become `rand_b`, so incremented ULIDs stay distinct.
+    /// The 6 random bits in between are lost; [`Ulid::is_uuid_v7_lossless()`] tells if they are all zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::new();
+    /// let uuid = u.to_uuid_v7();
+    ///
+    /// assert_eq!(uuid >> 80, u.to_u128() >> 80); // same timestamp
+    /// assert_eq!((uuid >> 76) & 0xF, 7); // version 7
+    /// ```
+    #[must_use]
+    pub const fn to_uuid_v7(self) -> u128 {
+        util::to_uuid_v7(self.0.get())
+    }
+
+    /// Converts a UUID v7 (as `u128`, like [`Ulid::from_u128()`]) into a `Ulid`.
+    ///
+    /// The inverse of [`Ulid::to_uuid_v7()`]: The version and variant bits are removed, and the 6 random
+    /// bits lost in the conversion to UUID v7 are zero. So converting a UUID v7 into a ULID and back is
+    /// always lossless, and keeps the time order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotUuidV7`] if the version or variant bits are not the ones of a UUID v7.
+    /// Returns [`Error::InvalidZero`] if timestamp and random bits are all zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let uuid = 0x0192_FA64_E41E_7B4A_85E0_3FD8_F28B_7522;
+    /// let u = Ulid::from_uuid_v7(uuid)?;
+    ///
+    /// assert_eq!(u.timestamp(), 0x0192_FA64_E41E);
+    /// assert_eq!(u.to_uuid_v7(), uuid);
+    /// # Ok::<(), mr_ulid::Error>(())
+    /// ```
+    pub const fn from_uuid_v7(uuid: u128) -> Result<Self, Error> {
+        match util::from_uuid_v7(uuid) {
+            Ok(n) => match Self::from_u128(n) {
+                Some(u) => Ok(u),
+                None => Err(Error::InvalidZero),
+            },
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns `true` if [`Ulid::to_uuid_v7()`] is lossless for this `Ulid`, i.e. converting back with
+    /// [`Ulid::from_uuid_v7()`] yields the same `Ulid`.
+    ///
+    /// This is the case if the 6 random bits below the top 12 random bits are all zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::new();
+    ///
+    /// assert_eq!(u.is_uuid_v7_lossless(), Ulid::from_uuid_v7(u.to_uuid_v7()) == Ok(u));
+    /// ```
+    #[must_use]
+    pub const fn is_uuid_v7_lossless(self) -> bool {
+        util::is_uuid_v7_lossless(self.0.get())
+    }
+
+    /// Returns an adaptor displaying the `Ulid` in lowercase, like formatting with `{:#}`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.display_lower().to_string(), "01jbx69s0ybd54br1zv3s8px92");
+    /// assert_eq!(format!("{u:#}"), u.display_lower().to_string());
+    /// ```
+    #[must_use]
+    pub const fn display_lower(self) -> DisplayLower {
+        DisplayLower::new(self.0.get())
+    }
+
+    /// Encodes a `Ulid` into its canonical string form, without allocation.
+    ///
+    /// The returned [`UlidString`] is stored inline and dereferences to [`str`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_u128(42).unwrap();
+    ///
+    /// assert_eq!(u.encode(), "0000000000000000000000001A");
+    /// assert_eq!(u.encode().as_str(), u.to_string());
+    /// ```
+    #[must_use]
+    pub fn encode(self) -> UlidString {
+        UlidString::new(self.0.get())
+    }
+
+    /// Encodes a `Ulid` into its canonical string form into the given buffer, without allocation.
+    ///
+    /// Returns the encoded string, borrowed from the buffer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_u128(42).unwrap();
+    /// let mut buffer = [0; 26];
+    ///
+    /// assert_eq!(u.encode_into(&mut buffer), "0000000000000000000000001A");
+    /// ```
+    pub fn encode_into(self, buffer: &mut [u8; 26]) -> &str {
+        base32::encode(self.0.get(), buffer)
+    }
+
+    /// Decodes a `Ulid` from the 26 ASCII bytes of its string form, like parsing with [`FromStr`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are no valid ULID. Zero values are rejected with [`Error::InvalidZero`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::Ulid;
+    ///
+    /// let u = Ulid::from_ascii(b"0000000000000000000000001A")?;
+    ///
+    /// assert_eq!(u.to_u128(), 42);
+    /// # Ok::<(), mr_ulid::Error>(())
+    /// ```
+    pub fn from_ascii(bytes: &[u8; 26]) -> Result<Self, Error> {
+        Self::from_u128(base32::decode(bytes)?).ok_or(Error::InvalidZero)
+    }
+
+    /// Parses a `Ulid` from a string, with options controlling how strict the parser is.
+    ///
+    /// With [`ParseOptions::DEFAULT`], this is the same as parsing with [`FromStr`]. Zero values are rejected with [`Error::InvalidZero`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not a valid ULID according to `options`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::{ParseOptions, Ulid};
+    ///
+    /// let u = Ulid::parse_with(" 01JB5C84ZB-M8QVBE5QR-ZW6HY89\n", ParseOptions::LENIENT)?;
+    /// assert_eq!(u.to_string(), "01JB5C84ZBM8QVBE5QRZW6HY89");
+    ///
+    /// assert!(Ulid::parse_with("01jb5c84zbm8qvbe5qrzw6hy89", ParseOptions::STRICT).is_err());
+    /// # Ok::<(), mr_ulid::Error>(())
+    /// ```
+    pub fn parse_with(s: &str, options: ParseOptions) -> Result<Self, Error> {
+        Self::from_u128(parse::parse(s, options)?).ok_or(Error::InvalidZero)
+    }
+
     /// Creates a `Ulid` from a `u128` integer.
     ///
     /// # Errors
@@ -356,7 +755,6 @@ impl Ulid {
     ///
     /// use mr_ulid::Ulid;
     ///
-    // cspell:disable-next-line
     /// let u = Ulid::from_u128(42)?;
     ///
     /// assert_eq!(u.to_non_zero_u128(), NonZero::new(42)?);
@@ -410,9 +808,35 @@ impl Ulid {
     /// assert!(u1.timestamp() <= u2.timestamp());
     /// # Some(()) }}
     /// ```
+    #[cfg(any(feature = "std", feature = "critical-section"))]
     #[must_use]
     pub fn try_new() -> Option<Self> {
-        Some(Self(NonZero::new(generator::generate()?)?))
+        Self::try_generate().ok()
+    }
+
+    /// Generates a new `Ulid`, reporting why generation failed.
+    ///
+    /// This is a variant of [`Ulid::try_new()`], which tells the cause of a failure,
+    /// e.g. to distinguish a misconfigured entropy source from a broken clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the case of problems with the ULID-generator (see [`GenerateError`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::{GenerateError, Ulid};
+    ///
+    /// match Ulid::try_generate() {
+    ///     Ok(u) => println!("generated {u}"),
+    ///     Err(GenerateError::ClockBeforeEpoch) => eprintln!("check the system clock"),
+    ///     Err(error) => eprintln!("failed to generate ULID: {error}"),
+    /// }
+    /// ```
+    #[cfg(any(feature = "std", feature = "critical-section"))]
+    pub fn try_generate() -> Result<Self, GenerateError> {
+        generator::generate().and_then(|n| Self::from_u128(n).ok_or(GenerateError::RandomnessOutOfRange))
     }
 
     /// Returns the timestamp part of a `Ulid` as a [`SystemTime`] and never panics.
@@ -431,6 +855,7 @@ impl Ulid {
     ///
     /// let datetime: Option<SystemTime> = u.try_datetime();
     /// ```
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn try_datetime(self) -> Option<SystemTime> {
         SystemTime::UNIX_EPOCH.checked_add(Duration::from_millis(self.timestamp()))
@@ -441,6 +866,7 @@ impl Ulid {
     /// While the blanket implementation of [`std::string::ToString`] for `std::fmt::Display` may
     /// panic, this method is guaranteed to never panic, but returns `None` if the string representation cannot be created.
     /// One reason this can happen is if the allocation of memory for the string fails.
+    #[cfg(feature = "alloc")]
     #[must_use]
     pub fn try_to_string(self) -> Option<String> {
         util::try_to_string(self.0.get())
@@ -488,6 +914,7 @@ impl Ulid {
     }
 }
 
+#[cfg(any(feature = "std", feature = "critical-section"))]
 impl Default for Ulid {
     fn default() -> Self {
         Self::new()
@@ -501,9 +928,27 @@ impl fmt::Debug for Ulid {
 }
 
 impl fmt::Display for Ulid {
+    /// Formats the ULID string, honoring width, fill and alignment. The alternate flag (`{:#}`) selects lowercase.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        util::display_ulid(self.0.get(), f)
+    }
+}
+
+impl fmt::LowerHex for Ulid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0.get(), f)
+    }
+}
+
+impl fmt::UpperHex for Ulid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0.get(), f)
+    }
+}
+
+impl fmt::Binary for Ulid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut buffer = [0; 26];
-        f.write_str(base32::encode(self.0.get(), &mut buffer))
+        fmt::Binary::fmt(&self.0.get(), f)
     }
 }
 
@@ -511,7 +956,7 @@ impl FromStr for Ulid {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let buffer = util::as_array(s.as_bytes())?;
-        Self::from_u128(base32::decode(buffer)?).ok_or(Error::InvalidZero)
+        Self::from_ascii(buffer)
     }
 }
 
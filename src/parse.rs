@@ -0,0 +1,360 @@
+use core::cmp::Ordering;
+
+use crate::{Error, base32, util};
+
+/// Options for parsing ULID strings, controlling how strict the parser is.
+///
+/// There are three presets:
+///
+/// - [`ParseOptions::STRICT`]: Accepts only the canonical form (uppercase, no `I`, `L`, or `O`).
+/// - [`ParseOptions::DEFAULT`]: Accepts lowercase letters and `I`, `L`, `O` for `1`, `1`, `0`, like [`FromStr`](core::str::FromStr).
+/// - [`ParseOptions::LENIENT`]: Additionally accepts copy-pasted ULIDs with surrounding whitespace,
+///   surrounding quotes, a `urn:ulid:` prefix, and hyphens anywhere (as in Crockford Base32).
+///
+/// The presets can be adjusted with the builder methods.
+///
+/// Errors carry byte offsets into the original input, so [`Error::with_input()`] works as expected.
+/// Only the lengths of [`Error::TooShort`] and [`Error::TooLong`] refer to the ULID after stripping
+/// whitespace, quotes, prefix and hyphens.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::{Error, ParseOptions, Ulid};
+///
+/// let s = " \"urn:ulid:01JB5C84ZB-M8QVBE5QR-ZW6HY89\" ";
+/// let u = Ulid::parse_with(s, ParseOptions::LENIENT)?;
+/// assert_eq!(u.to_string(), "01JB5C84ZBM8QVBE5QRZW6HY89");
+///
+/// let s = "01jb5c84zbm8qvbe5qrzw6hy89";
+/// assert!(Ulid::parse_with(s, ParseOptions::DEFAULT).is_ok());
+/// assert_eq!(
+///     Ulid::parse_with(s, ParseOptions::STRICT),
+///     Err(Error::NonCanonicalChar { offset: 2, byte: b'j' })
+/// );
+///
+/// let options = ParseOptions::DEFAULT.trim_whitespace(true);
+/// assert!(Ulid::parse_with(" 01JB5C84ZBM8QVBE5QRZW6HY89\n", options).is_ok());
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(clippy::struct_excessive_bools)] // Independent flags
+pub struct ParseOptions {
+    canonical_only: bool,
+    trim_whitespace: bool,
+    strip_quotes: bool,
+    strip_urn_prefix: bool,
+    ignore_hyphens: bool,
+}
+
+/// Prefix of ULIDs as URNs, stripped by [`ParseOptions::strip_urn_prefix()`].
+const URN_PREFIX: &str = "urn:ulid:";
+
+impl ParseOptions {
+    /// Accepts only ULIDs in canonical form.
+    pub const STRICT: Self = Self {
+        canonical_only: true,
+        ..Self::DEFAULT
+    };
+
+    /// Accepts ULIDs like [`FromStr`](core::str::FromStr): Case-insensitive, with `I`, `L`, `O` as aliases for `1` and `0`.
+    pub const DEFAULT: Self = Self {
+        canonical_only: false,
+        trim_whitespace: false,
+        strip_quotes: false,
+        strip_urn_prefix: false,
+        ignore_hyphens: false,
+    };
+
+    /// Accepts ULIDs with surrounding whitespace and quotes, a `urn:ulid:` prefix, and hyphens.
+    pub const LENIENT: Self = Self {
+        canonical_only: false,
+        trim_whitespace: true,
+        strip_quotes: true,
+        strip_urn_prefix: true,
+        ignore_hyphens: true,
+    };
+
+    /// Accepts only uppercase letters and no aliases (`I`, `L`, `O`), rejecting other characters
+    /// with [`Error::NonCanonicalChar`].
+    #[must_use]
+    pub const fn canonical_only(mut self, enabled: bool) -> Self {
+        self.canonical_only = enabled;
+        self
+    }
+
+    /// Ignores leading and trailing whitespace.
+    #[must_use]
+    pub const fn trim_whitespace(mut self, enabled: bool) -> Self {
+        self.trim_whitespace = enabled;
+        self
+    }
+
+    /// Ignores surrounding double quotes (`"`) or single quotes (`'`).
+    #[must_use]
+    pub const fn strip_quotes(mut self, enabled: bool) -> Self {
+        self.strip_quotes = enabled;
+        self
+    }
+
+    /// Ignores a leading `urn:ulid:` prefix (case-insensitive).
+    #[must_use]
+    pub const fn strip_urn_prefix(mut self, enabled: bool) -> Self {
+        self.strip_urn_prefix = enabled;
+        self
+    }
+
+    /// Ignores hyphens (`-`) anywhere in the ULID, as allowed by Crockford Base32 for readability.
+    #[must_use]
+    pub const fn ignore_hyphens(mut self, enabled: bool) -> Self {
+        self.ignore_hyphens = enabled;
+        self
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Parses a ULID string according to `options`, returning the value of the ULID.
+pub(crate) fn parse(input: &str, options: ParseOptions) -> Result<u128, Error> {
+    let (start, ulid) = strip(input, options);
+
+    let result = gather(ulid, options).and_then(|buffer| {
+        let n = base32::decode(&buffer)?;
+
+        if options.canonical_only {
+            if let Some(offset) = buffer.iter().position(|&c| !is_canonical_char(c)) {
+                let byte = buffer[offset];
+                return Err(Error::NonCanonicalChar { offset, byte });
+            }
+        }
+
+        Ok(n)
+    });
+
+    result.map_err(|error| remap(error, ulid, start, options))
+}
+
+/// Strips whitespace, quotes and the URN prefix, returning the offset and the remaining part of `input`.
+fn strip(input: &str, options: ParseOptions) -> (usize, &str) {
+    let mut start = 0;
+    let mut s = input;
+
+    let trim = |s: &mut &str, start: &mut usize| {
+        if options.trim_whitespace {
+            let trimmed = s.trim_start();
+            *start += s.len() - trimmed.len();
+            *s = trimmed.trim_end();
+        }
+    };
+
+    trim(&mut s, &mut start);
+
+    if options.strip_quotes {
+        let quoted = ['"', '\'']
+            .into_iter()
+            .find_map(|quote| s.strip_prefix(quote)?.strip_suffix(quote));
+        if let Some(inner) = quoted {
+            s = inner;
+            start += 1;
+            trim(&mut s, &mut start);
+        }
+    }
+
+    if options.strip_urn_prefix
+        && s.get(..URN_PREFIX.len())
+            .is_some_and(|p| p.eq_ignore_ascii_case(URN_PREFIX))
+    {
+        s = &s[URN_PREFIX.len()..];
+        start += URN_PREFIX.len();
+    }
+
+    (start, s)
+}
+
+/// Collects the 26 characters of the ULID, skipping hyphens if enabled.
+fn gather(ulid: &str, options: ParseOptions) -> Result<[u8; 26], Error> {
+    if !options.ignore_hyphens {
+        return util::as_array(ulid.as_bytes()).copied();
+    }
+
+    let mut buffer = [0; 26];
+    let mut len = 0;
+
+    for byte in ulid.bytes().filter(|&byte| byte != b'-') {
+        if let Some(slot) = buffer.get_mut(len) {
+            *slot = byte;
+        }
+        len += 1;
+    }
+
+    match len.cmp(&buffer.len()) {
+        Ordering::Equal => Ok(buffer),
+        Ordering::Less => Err(Error::TooShort {
+            expected: buffer.len(),
+            actual: len,
+        }),
+        Ordering::Greater => Err(Error::TooLong {
+            expected: buffer.len(),
+            actual: len,
+        }),
+    }
+}
+
+/// Maps the offsets of errors from the gathered ULID back to offsets into the original input.
+fn remap(error: Error, ulid: &str, start: usize, options: ParseOptions) -> Error {
+    let original = |index: usize| {
+        let skipped = if options.ignore_hyphens {
+            ulid.bytes()
+                .enumerate()
+                .filter(|&(_, byte)| byte != b'-')
+                .nth(index)
+                .map_or(index, |(position, _)| position)
+        } else {
+            index
+        };
+        start + skipped
+    };
+
+    match error {
+        Error::InvalidChar { offset, byte } => Error::InvalidChar {
+            offset: original(offset),
+            byte,
+        },
+        Error::Overflow { offset, byte } => Error::Overflow {
+            offset: original(offset),
+            byte,
+        },
+        Error::NonCanonicalChar { offset, byte } => Error::NonCanonicalChar {
+            offset: original(offset),
+            byte,
+        },
+        other => other,
+    }
+}
+
+const fn is_canonical_char(c: u8) -> bool {
+    matches!(c, b'0'..=b'9' | b'A'..=b'H' | b'J' | b'K' | b'M' | b'N' | b'P'..=b'T' | b'V'..=b'Z')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ULID: &str = "01JB5C84ZBM8QVBE5QRZW6HY89";
+
+    fn expected() -> u128 {
+        base32::decode(ULID.as_bytes().try_into().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_strict() {
+        assert_eq!(parse(ULID, ParseOptions::STRICT), Ok(expected()));
+
+        assert_eq!(
+            parse("01JB5C84ZBM8QVBE5QRZW6HY8l", ParseOptions::STRICT),
+            Err(Error::NonCanonicalChar { offset: 25, byte: b'l' })
+        );
+        assert_eq!(
+            parse("o1JB5C84ZBM8QVBE5QRZW6HY89", ParseOptions::STRICT),
+            Err(Error::NonCanonicalChar { offset: 0, byte: b'o' })
+        );
+        // Invalid characters are reported as such
+        assert_eq!(
+            parse("01JB5C84ZBM8QVBE5QRZW6HYU9", ParseOptions::STRICT),
+            Err(Error::InvalidChar { offset: 24, byte: b'U' })
+        );
+        assert_eq!(
+            parse(&format!(" {ULID}"), ParseOptions::STRICT),
+            Err(Error::TooLong {
+                expected: 26,
+                actual: 27
+            })
+        );
+    }
+
+    #[test]
+    fn test_default() {
+        assert_eq!(parse(ULID, ParseOptions::DEFAULT), Ok(expected()));
+        assert_eq!(parse(&ULID.to_lowercase(), ParseOptions::DEFAULT), Ok(expected()));
+        assert_eq!(
+            parse(&format!("{ULID}\n"), ParseOptions::DEFAULT),
+            Err(Error::TooLong {
+                expected: 26,
+                actual: 27
+            })
+        );
+    }
+
+    #[test]
+    fn test_lenient() {
+        let inputs = [
+            " 01JB5C84ZBM8QVBE5QRZW6HY89\n",
+            "\"01JB5C84ZBM8QVBE5QRZW6HY89\"",
+            "'01JB5C84ZBM8QVBE5QRZW6HY89'",
+            "\" 01JB5C84ZBM8QVBE5QRZW6HY89 \"",
+            "urn:ulid:01JB5C84ZBM8QVBE5QRZW6HY89",
+            "URN:ULID:01jb5c84zbm8qvbe5qrzw6hy89",
+            "01JB5-C84ZB-M8QVB-E5QRZ-W6HY8-9",
+            "\t\"urn:ulid:01JB5C84-ZBM8QVBE-5QRZW6HY-89\" ",
+        ];
+
+        for input in inputs {
+            assert_eq!(parse(input, ParseOptions::LENIENT), Ok(expected()), "{input:?}");
+            assert!(parse(input, ParseOptions::DEFAULT).is_err(), "{input:?}");
+        }
+
+        // Mismatched quotes are not stripped
+        assert_eq!(
+            parse("\"01JB5C84ZBM8QVBE5QRZW6HY89'", ParseOptions::LENIENT),
+            Err(Error::TooLong {
+                expected: 26,
+                actual: 28
+            })
+        );
+        assert_eq!(
+            parse("01JB5C84ZBM8QVBE5QRZW6HY8", ParseOptions::LENIENT),
+            Err(Error::TooShort {
+                expected: 26,
+                actual: 25
+            })
+        );
+    }
+
+    #[test]
+    fn test_lenient_offsets() {
+        let input = " \"urn:ulid:01JB5-C84ZB-M8QVB-E5QRZ-W6HY8-$\" ";
+        let error = parse(input, ParseOptions::LENIENT).unwrap_err();
+        assert_eq!(error, Error::InvalidChar { offset: 41, byte: b'$' });
+        assert_eq!(&input[41..42], "$");
+
+        let input = " urn:ulid:81JB5C84ZBM8QVBE5QRZW6HY89";
+        let error = parse(input, ParseOptions::LENIENT).unwrap_err();
+        assert_eq!(error, Error::Overflow { offset: 10, byte: b'8' });
+
+        let options = ParseOptions::LENIENT.canonical_only(true);
+        let input = "-01JB5-c84ZB-M8QVB-E5QRZ-W6HY8-9";
+        let error = parse(input, options).unwrap_err();
+        assert_eq!(error, Error::NonCanonicalChar { offset: 7, byte: b'c' });
+    }
+
+    #[test]
+    fn test_builder() {
+        assert_eq!(ParseOptions::default(), ParseOptions::DEFAULT);
+        assert_eq!(ParseOptions::DEFAULT.canonical_only(true), ParseOptions::STRICT);
+
+        let options = ParseOptions::DEFAULT
+            .trim_whitespace(true)
+            .strip_quotes(true)
+            .strip_urn_prefix(true)
+            .ignore_hyphens(true);
+        assert_eq!(options, ParseOptions::LENIENT);
+
+        let options = ParseOptions::LENIENT.strip_urn_prefix(false);
+        assert!(parse(&format!("urn:ulid:{ULID}"), options).is_err());
+        assert!(parse(&format!("'{ULID}'"), options).is_ok());
+    }
+}
@@ -0,0 +1,307 @@
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    str::{FromStr, from_utf8_unchecked},
+};
+
+use crate::{Error, PrefixedUlidError, Ulid, ZeroableUlid, base32};
+
+/// Maximum length of a prefix in bytes. Also used by `TypeId`.
+pub(crate) const MAX_PREFIX_LEN: usize = 63;
+
+/// Maximum length of a prefixed string: prefix, underscore, and ULID. Also used by `TypeId`.
+pub(crate) const MAX_LEN: usize = MAX_PREFIX_LEN + 1 + 26;
+
+/// The prefix of a [`PrefixedUlid`], usually implemented by a marker type.
+///
+/// Prefixes consist of 1 to 63 lowercase ASCII letters, digits and underscores, start with a letter,
+/// and don't end with an underscore. Invalid prefixes are rejected at compile time, when a
+/// `PrefixedUlid` with the prefix is created.
+pub trait UlidPrefix {
+    /// The prefix, without the separating underscore, e.g. `"user"`.
+    const PREFIX: &'static str;
+}
+
+/// A ULID with a type-specific prefix, formatted as `<prefix>_<ulid>`, e.g. `user_01JB5C84ZBM8QVBE5QRZW6HY89`.
+///
+/// The prefix is part of the type, given by a marker type implementing [`UlidPrefix`], so a
+/// `PrefixedUlid<Order>` can't be passed where a `PrefixedUlid<User>` is expected. Parsing validates the prefix,
+/// and distinguishes a wrong prefix from a malformed ULID (see [`PrefixedUlidError`]).
+///
+/// `PrefixedUlid` has the same size as `Ulid`, and `Option<PrefixedUlid<P>>` too. With the `serde` feature,
+/// it is serialized as its string form.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::{PrefixedUlid, PrefixedUlidError, UlidPrefix};
+///
+/// struct User;
+///
+/// impl UlidPrefix for User {
+///     const PREFIX: &'static str = "user";
+/// }
+///
+/// type UserId = PrefixedUlid<User>;
+///
+/// let id = UserId::new();
+/// let s = id.to_string();
+/// assert!(s.starts_with("user_"));
+///
+/// let parsed: UserId = s.parse()?;
+/// assert_eq!(parsed, id);
+///
+/// // cspell:disable-next-line
+/// let error = "order_01JB5C84ZBM8QVBE5QRZW6HY89".parse::<UserId>().unwrap_err();
+/// assert_eq!(error, PrefixedUlidError::WrongPrefix { expected: "user" });
+/// # Ok::<(), PrefixedUlidError>(())
+/// ```
+pub struct PrefixedUlid<P> {
+    ulid: Ulid,
+    prefix: PhantomData<fn() -> P>,
+}
+
+impl<P: UlidPrefix> PrefixedUlid<P> {
+    /// Evaluated at compile time whenever a `PrefixedUlid` is created, rejecting invalid prefixes.
+    const VALID_PREFIX: () = assert!(is_valid_prefix(P::PREFIX.as_bytes(), true), "invalid ULID prefix");
+
+    /// Generates a new unique `PrefixedUlid`, like [`Ulid::new()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ULID can't be generated, see [`Ulid::new()`].
+    #[cfg(any(feature = "std", feature = "critical-section"))]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_ulid(Ulid::new())
+    }
+
+    /// Creates a `PrefixedUlid` from a `Ulid`.
+    #[must_use]
+    pub const fn from_ulid(ulid: Ulid) -> Self {
+        let () = Self::VALID_PREFIX;
+        Self {
+            ulid,
+            prefix: PhantomData,
+        }
+    }
+
+    /// Returns the `Ulid` without the prefix.
+    #[must_use]
+    pub const fn ulid(self) -> Ulid {
+        self.ulid
+    }
+
+    /// Returns the prefix, without the separating underscore.
+    #[must_use]
+    pub const fn prefix() -> &'static str {
+        P::PREFIX
+    }
+
+    pub(crate) fn encode(self, buffer: &mut [u8; MAX_LEN]) -> &str {
+        encode(P::PREFIX, self.ulid.to_u128(), buffer)
+    }
+}
+
+/// Encodes `<prefix>_<ulid>` into `buffer`. Panics if the prefix is longer than 63 bytes.
+fn encode<'a>(prefix: &str, ulid: u128, buffer: &'a mut [u8; MAX_LEN]) -> &'a str {
+    let mut suffix = [0; 26];
+    encode_prefixed(prefix.as_bytes(), base32::encode(ulid, &mut suffix), buffer)
+}
+
+/// Writes `<prefix>_<suffix>` into `buffer`, or only the suffix if the prefix is empty. Also used by `TypeId`.
+///
+/// Panics if the prefix is longer than 63 bytes.
+pub(crate) fn encode_prefixed<'a>(prefix: &[u8], suffix: &str, buffer: &'a mut [u8; MAX_LEN]) -> &'a str {
+    let start = if prefix.is_empty() { 0 } else { prefix.len() + 1 };
+
+    buffer[..prefix.len()].copy_from_slice(prefix);
+    buffer[prefix.len()] = b'_';
+    buffer[start..start + 26].copy_from_slice(suffix.as_bytes());
+
+    // Safety: Prefixes are ASCII, and so are encoded ULIDs
+    unsafe { from_utf8_unchecked(&buffer[..start + 26]) }
+}
+
+/// Formats `<prefix>_<ulid>`, honoring width, fill and alignment. Also used by `#[derive(UlidNewtype)]`.
+///
+/// # Errors
+///
+/// Returns an error if writing to the formatter fails.
+pub fn fmt_prefixed(prefix: &str, ulid: u128, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut buffer = [0; MAX_LEN];
+    f.pad(encode(prefix, ulid, &mut buffer))
+}
+
+/// Parses `<prefix>_<ulid>`, returning the ULID as `u128`, which may be zero. Also used by `#[derive(UlidNewtype)]`.
+///
+/// # Errors
+///
+/// Returns [`PrefixedUlidError::WrongPrefix`] if the input doesn't start with the prefix and an underscore,
+/// and [`PrefixedUlidError::InvalidUlid`] if the ULID is malformed.
+pub fn parse_prefixed(prefix: &'static str, s: &str) -> Result<u128, PrefixedUlidError> {
+    let wrong_prefix = PrefixedUlidError::WrongPrefix { expected: prefix };
+    let ulid = s
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .ok_or(wrong_prefix)?;
+
+    let offset = prefix.len() + 1;
+
+    // ULIDs never contain underscores, so one in the rest either belongs to a longer prefix
+    // (e.g. `user_account_<ulid>` for the prefix `user`), or is an invalid character of the ULID
+    if let Some(position) = ulid.find('_') {
+        let is_longer_prefix = s
+            .rsplit_once('_')
+            .is_some_and(|(actual, ulid)| ulid.len() == 26 && is_valid_prefix(actual.as_bytes(), true));
+        if is_longer_prefix {
+            return Err(wrong_prefix);
+        }
+        return Err(PrefixedUlidError::InvalidUlid(Error::InvalidChar {
+            offset: offset + position,
+            byte: b'_',
+        }));
+    }
+
+    ulid.parse()
+        .map(ZeroableUlid::to_u128)
+        .map_err(|error: Error| PrefixedUlidError::InvalidUlid(error.shifted(offset)))
+}
+
+/// Returns `true` if `prefix` is 1 to 63 lowercase ASCII letters, underscores and, if `digits` is set, digits,
+/// starts with a letter, and doesn't end with an underscore.
+///
+/// Shared by `PrefixedUlid` and `#[derive(UlidNewtype)]` (with digits), and `TypeId` (without digits).
+#[must_use]
+pub const fn is_valid_prefix(prefix: &[u8], digits: bool) -> bool {
+    let [first, .., last] = prefix else {
+        return matches!(prefix, [b'a'..=b'z']);
+    };
+    if prefix.len() > MAX_PREFIX_LEN || !first.is_ascii_lowercase() || *last == b'_' {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < prefix.len() {
+        match prefix[i] {
+            b'a'..=b'z' | b'_' => {}
+            b'0'..=b'9' if digits => {}
+            _ => return false,
+        }
+        i += 1;
+    }
+    true
+}
+
+#[cfg(any(feature = "std", feature = "critical-section"))]
+impl<P: UlidPrefix> Default for PrefixedUlid<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P> Clone for PrefixedUlid<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for PrefixedUlid<P> {}
+
+impl<P> PartialEq for PrefixedUlid<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ulid == other.ulid
+    }
+}
+
+impl<P> Eq for PrefixedUlid<P> {}
+
+impl<P> PartialOrd for PrefixedUlid<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P> Ord for PrefixedUlid<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ulid.cmp(&other.ulid)
+    }
+}
+
+impl<P> Hash for PrefixedUlid<P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ulid.hash(state);
+    }
+}
+
+impl<P: UlidPrefix> fmt::Debug for PrefixedUlid<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = [0; MAX_LEN];
+        f.debug_tuple("PrefixedUlid").field(&self.encode(&mut buffer)).finish()
+    }
+}
+
+impl<P: UlidPrefix> fmt::Display for PrefixedUlid<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_prefixed(P::PREFIX, self.ulid.to_u128(), f)
+    }
+}
+
+impl<P: UlidPrefix> FromStr for PrefixedUlid<P> {
+    type Err = PrefixedUlidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ulid = parse_prefixed(P::PREFIX, s)?;
+        Ulid::from_u128(ulid)
+            .map(Self::from_ulid)
+            .ok_or(PrefixedUlidError::InvalidUlid(Error::InvalidZero))
+    }
+}
+
+impl<P: UlidPrefix> From<Ulid> for PrefixedUlid<P> {
+    fn from(ulid: Ulid) -> Self {
+        Self::from_ulid(ulid)
+    }
+}
+
+impl<P> From<PrefixedUlid<P>> for Ulid {
+    fn from(prefixed: PrefixedUlid<P>) -> Self {
+        prefixed.ulid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_prefix() {
+        for valid in ["u", "user", "user_account", "v2", "a_b_c"] {
+            assert!(is_valid_prefix(valid.as_bytes(), true), "{valid}");
+        }
+        for invalid in [
+            "",
+            "_",
+            "User",
+            "2fa",
+            "user_",
+            "_user",
+            "us-er",
+            "usér",
+            &"a".repeat(64),
+        ] {
+            assert!(!is_valid_prefix(invalid.as_bytes(), true), "{invalid}");
+        }
+        assert!(is_valid_prefix("a".repeat(63).as_bytes(), true));
+
+        // Without digits, as for TypeIDs
+        for valid in ["u", "user", "user_account"] {
+            assert!(is_valid_prefix(valid.as_bytes(), false), "{valid}");
+        }
+        for invalid in ["v2", "user_2", "user2_account"] {
+            assert!(!is_valid_prefix(invalid.as_bytes(), false), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+    ops::RangeInclusive,
+    str::FromStr,
+};
+
+use crate::{EntropySource, EntropySourceHandle};
+
+/// First line of every entropy log, identifying the format and its version.
+const HEADER: &str = "mr-ulid entropy log v1";
+
+/// An entropy source which records everything another entropy source returns.
+///
+/// The recorded log can be fed into a [`ReplaySource`] to reproduce exactly the same sequence of ULIDs,
+/// e.g. to reproduce a failure of a test or a production incident.
+///
+/// The log is a text format, stable across versions of this crate. It starts with the line
+/// `mr-ulid entropy log v1`, followed by one line per call of the entropy source:
+///
+/// - `t <millis>` for [`EntropySource::timestamp()`],
+/// - `n <nanos>` for [`EntropySource::timestamp_nanos()`],
+/// - `r <start> <end> <value>` for [`EntropySource::random()`] with the requested range,
+///
+/// where a `-` replaces the result if the entropy source returned `None`. All numbers are decimal.
+///
+/// The writer is flushed after every line, so the log is complete even if the process crashes.
+/// If writing fails, the entropy source returns `None`, failing the generation of the ULID.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::{EntropySourceHandle, RecordingSource, ReplaySource, STANDARD_ENTROPY_SOURCE};
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # let path = std::env::temp_dir().join(format!("mr-ulid-doc-replay-{}", std::process::id()));
+///
+/// let log = std::fs::File::create(&path)?;
+/// let source = RecordingSource::new(STANDARD_ENTROPY_SOURCE, log)?;
+/// mr_ulid::set_entropy_source(EntropySourceHandle::new(source));
+///
+/// // ... generate ULIDs ...
+///
+/// let log = std::io::BufReader::new(std::fs::File::open(&path)?);
+/// let source = ReplaySource::new(log)?;
+/// mr_ulid::set_entropy_source(EntropySourceHandle::new(source));
+///
+/// // ... generate the same ULIDs again ...
+/// # mr_ulid::set_entropy_source(STANDARD_ENTROPY_SOURCE);
+/// # std::fs::remove_file(&path)?;
+/// # Ok(()) }
+/// ```
+pub struct RecordingSource<W> {
+    inner: EntropySourceHandle,
+    writer: W,
+}
+
+impl<W: Write> RecordingSource<W> {
+    /// Creates a `RecordingSource` recording the entropy source `inner` into `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header of the log can't be written.
+    pub fn new(inner: EntropySourceHandle, mut writer: W) -> io::Result<Self> {
+        writeln!(writer, "{HEADER}")?;
+        writer.flush()?;
+
+        Ok(Self { inner, writer })
+    }
+
+    fn record<T>(&mut self, result: Option<T>, args: fmt::Arguments<'_>) -> Option<T> {
+        let written = writeln!(self.writer, "{args}").and_then(|()| self.writer.flush());
+
+        written.ok().and(result)
+    }
+}
+
+impl<W: Write + Send> EntropySource for RecordingSource<W> {
+    fn timestamp(&mut self) -> Option<u64> {
+        let millis = self.inner.timestamp();
+        self.record(millis, format_args!("t {}", Value(millis)))
+    }
+
+    fn random(&mut self, range: RangeInclusive<u128>) -> Option<u128> {
+        let (start, end) = (*range.start(), *range.end());
+        let value = self.inner.random(range);
+        self.record(value, format_args!("r {start} {end} {}", Value(value)))
+    }
+
+    fn timestamp_nanos(&mut self) -> Option<u128> {
+        let nanos = self.inner.timestamp_nanos();
+        self.record(nanos, format_args!("n {}", Value(nanos)))
+    }
+}
+
+impl<W> fmt::Debug for RecordingSource<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RecordingSource { ... }")
+    }
+}
+
+/// An entropy source replaying a log recorded by a [`RecordingSource`].
+///
+/// Each call of the entropy source consumes the next line of the log. As long as ULIDs are generated in
+/// the same way as during recording, the replayed ULIDs are identical to the recorded ones.
+///
+/// Once the calls diverge from the log (a different kind of call or a different range of random numbers),
+/// the log is exhausted, or a line is malformed, this entropy source returns `None` for all further calls.
+pub struct ReplaySource<R> {
+    reader: R,
+    line: String,
+    diverged: bool,
+}
+
+impl<R: BufRead> ReplaySource<R> {
+    /// Creates a `ReplaySource` reading the log from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log can't be read or doesn't start with the expected header.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+
+        if line.trim_end() != HEADER {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an entropy log"));
+        }
+
+        Ok(Self {
+            reader,
+            line,
+            diverged: false,
+        })
+    }
+
+    /// Returns `true` if the calls diverged from the log, or the log is exhausted.
+    #[must_use]
+    pub const fn has_diverged(&self) -> bool {
+        self.diverged
+    }
+
+    /// Reads the next line of the log, which is expected to start with `expected`, and parses the recorded result.
+    ///
+    /// Returns `None` if the recorded result is `None`, or if the calls diverged from the log.
+    fn replay<T: FromStr>(&mut self, expected: &str) -> Option<T> {
+        if self.diverged {
+            return None;
+        }
+
+        self.line.clear();
+        let recorded = match self.reader.read_line(&mut self.line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => self
+                .line
+                .trim_end()
+                .strip_prefix(expected)
+                .and_then(|rest| rest.strip_prefix(' ')),
+        };
+
+        match recorded {
+            Some("-") => None,
+            Some(value) => {
+                let value = value.parse().ok();
+                self.diverged = value.is_none();
+                value
+            }
+            None => {
+                self.diverged = true;
+                None
+            }
+        }
+    }
+}
+
+impl<R: BufRead + Send> EntropySource for ReplaySource<R> {
+    fn timestamp(&mut self) -> Option<u64> {
+        self.replay("t")
+    }
+
+    fn random(&mut self, range: RangeInclusive<u128>) -> Option<u128> {
+        let expected = format!("r {} {}", range.start(), range.end());
+        self.replay(&expected)
+    }
+
+    fn timestamp_nanos(&mut self) -> Option<u128> {
+        self.replay("n")
+    }
+}
+
+impl<R> fmt::Debug for ReplaySource<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ReplaySource { ... }")
+    }
+}
+
+/// Formats a recorded result, with `-` for `None`.
+struct Value<T>(Option<T>);
+
+impl<T: fmt::Display> fmt::Display for Value<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(value) => write!(f, "{value}"),
+            None => f.write_str("-"),
+        }
+    }
+}
+
+#[cfg(any(feature = "rand", feature = "getrandom"))]
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+    use crate::{GenerateError, STANDARD_ENTROPY_SOURCE, generator::Generator};
+
+    /// A writer whose content is still accessible after moving it into an entropy source.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let buffer = SharedBuffer::default();
+
+        let source = RecordingSource::new(STANDARD_ENTROPY_SOURCE, buffer.clone()).unwrap();
+        let mut generator = Generator::new(EntropySourceHandle::new(source));
+        let recorded: Vec<_> = (0..100).map(|_| generator.next_ulid().unwrap()).collect();
+
+        let log = buffer.0.lock().unwrap().clone();
+        let source = ReplaySource::new(Cursor::new(log)).unwrap();
+        let mut generator = Generator::new(EntropySourceHandle::new(source));
+        let replayed: Vec<_> = (0..100).map(|_| generator.next_ulid().unwrap()).collect();
+
+        assert_eq!(recorded, replayed);
+
+        // The log is exhausted
+        assert_eq!(generator.next_ulid(), Err(GenerateError::TimestampUnavailable));
+    }
+
+    #[test]
+    fn test_format() {
+        let log = "mr-ulid entropy log v1\nt 1000\nr 0 99 42\nn -\nt 1001\n";
+        let mut source = ReplaySource::new(Cursor::new(log)).unwrap();
+
+        assert_eq!(source.timestamp(), Some(1000));
+        assert_eq!(source.random(0..=99), Some(42));
+        assert_eq!(source.timestamp_nanos(), None);
+        assert!(!source.has_diverged());
+
+        // A different kind of call diverges from the log
+        assert_eq!(source.random(0..=99), None);
+        assert!(source.has_diverged());
+        assert_eq!(source.timestamp(), None);
+
+        let mut buffer = Vec::new();
+        let mut source = RecordingSource::new(
+            EntropySourceHandle::new(ReplaySource::new(Cursor::new(log)).unwrap()),
+            &mut buffer,
+        )
+        .unwrap();
+        source.timestamp();
+        source.random(0..=99);
+        source.timestamp_nanos();
+        source.random(0..=99);
+        drop(source);
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "mr-ulid entropy log v1\nt 1000\nr 0 99 42\nn -\nr 0 99 -\n"
+        );
+    }
+
+    #[test]
+    fn test_random_range_mismatch() {
+        let log = "mr-ulid entropy log v1\nr 0 99 42\n";
+        let mut source = ReplaySource::new(Cursor::new(log)).unwrap();
+
+        assert_eq!(source.random(0..=100), None);
+        assert!(source.has_diverged());
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let error = ReplaySource::new(Cursor::new("garbage\n")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
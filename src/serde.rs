@@ -1,11 +1,11 @@
-use std::fmt;
+use core::{fmt, marker::PhantomData};
 
 use serde::{
     Deserialize, Serialize, Serializer,
     de::{self, Deserializer, Visitor},
 };
 
-use crate::{Ulid, ZeroableUlid, base32};
+use crate::{Id, PrefixedUlid, TypeId, Ulid, UlidPrefix, UlidString, ZeroableUlid, base32, prefixed};
 
 impl Serialize for ZeroableUlid {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -68,3 +68,132 @@ impl<'de> Deserialize<'de> for Ulid {
         deserializer.deserialize_str(NonZeroVisitor)
     }
 }
+
+impl Serialize for UlidString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for UlidString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        ZeroableUlid::deserialize(deserializer).map(ZeroableUlid::encode)
+    }
+}
+
+impl<P: UlidPrefix> Serialize for PrefixedUlid<P> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut buffer = [0; prefixed::MAX_LEN];
+        serializer.serialize_str(self.encode(&mut buffer))
+    }
+}
+
+impl<'de, P: UlidPrefix> Deserialize<'de> for PrefixedUlid<P> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PrefixedVisitor<P>(PhantomData<fn() -> P>);
+
+        impl<P: UlidPrefix> Visitor<'_> for PrefixedVisitor<P> {
+            type Value = PrefixedUlid<P>;
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a valid ULID string with the prefix '{}_'", P::PREFIX)
+            }
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                value.parse().map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_str(PrefixedVisitor(PhantomData))
+    }
+}
+
+impl Serialize for TypeId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut buffer = [0; prefixed::MAX_LEN];
+        serializer.serialize_str(self.encode(&mut buffer))
+    }
+}
+
+impl<'de> Deserialize<'de> for TypeId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TypeIdVisitor;
+
+        impl Visitor<'_> for TypeIdVisitor {
+            type Value = TypeId;
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid TypeID string")
+            }
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                value.parse().map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_str(TypeIdVisitor)
+    }
+}
+
+impl<T> Serialize for Id<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.ulid().serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Id<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ulid::deserialize(deserializer).map(Self::from_ulid)
+    }
+}
+
+/// Deserializes a value from a string by its `FromStr` implementation. Used by `#[derive(UlidNewtype)]`.
+///
+/// # Errors
+///
+/// Returns an error if the input is no string, or can't be parsed.
+#[cfg(feature = "derive")]
+pub fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: core::str::FromStr,
+    T::Err: fmt::Display,
+{
+    struct FromStrVisitor<T>(PhantomData<fn() -> T>);
+
+    impl<T> Visitor<'_> for FromStrVisitor<T>
+    where
+        T: core::str::FromStr,
+        T::Err: fmt::Display,
+    {
+        type Value = T;
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a valid ULID string")
+        }
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            value.parse().map_err(de::Error::custom)
+        }
+    }
+
+    deserializer.deserialize_str(FromStrVisitor(PhantomData))
+}
@@ -0,0 +1,121 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use fs4::FileExt;
+
+use crate::base32;
+
+/// The last generated ULID, shared with other processes via a locked file.
+///
+/// The file contains the last generated ULID as a 26 characters string,
+/// or is empty if no ULID was generated yet.
+pub(crate) struct SharedState {
+    file: File,
+}
+
+impl SharedState {
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        Ok(Self { file })
+    }
+
+    /// Locks the file exclusively and replaces the stored ULID with the one returned by `f`.
+    ///
+    /// `f` gets the stored ULID (zero for an empty file) and may return `None` to keep it unchanged.
+    pub(crate) fn update(&mut self, f: impl FnOnce(u128) -> Option<u128>) -> io::Result<Option<u128>> {
+        FileExt::lock(&self.file)?;
+
+        let result = self.read().map(f).and_then(|ulid| {
+            if let Some(ulid) = ulid {
+                self.write(ulid)?;
+            }
+            Ok(ulid)
+        });
+
+        FileExt::unlock(&self.file)?;
+
+        result
+    }
+
+    fn read(&mut self) -> io::Result<u128> {
+        let mut buffer = [0; 27];
+        let mut len = 0;
+
+        self.file.seek(SeekFrom::Start(0))?;
+
+        while len < buffer.len() {
+            match self.file.read(&mut buffer[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+
+        match len {
+            0 => Ok(0),
+            26 => base32::decode(buffer[..26].try_into().unwrap())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "not a ULID")),
+        }
+    }
+
+    fn write(&mut self, ulid: u128) -> io::Result<()> {
+        let mut buffer = [0; 26];
+
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(base32::encode(ulid, &mut buffer).as_bytes())?;
+        self.file.set_len(26)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("mr-ulid-{name}-{pid}", pid = std::process::id()))
+    }
+
+    #[test]
+    fn test_update() {
+        let path = temp_path("test-update");
+        let _ = std::fs::remove_file(&path);
+
+        let mut state = SharedState::open(&path).unwrap();
+
+        assert_eq!(state.update(|last| Some(last + 42)).unwrap(), Some(42));
+        assert_eq!(state.update(|last| Some(last + 1)).unwrap(), Some(43));
+        assert_eq!(state.update(|_| None).unwrap(), None);
+
+        let mut other = SharedState::open(&path).unwrap();
+        assert_eq!(other.update(Some).unwrap(), Some(43));
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "0000000000000000000000001B");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_content() {
+        let path = temp_path("test-invalid-content");
+        std::fs::write(&path, "garbage").unwrap();
+
+        let mut state = SharedState::open(&path).unwrap();
+        let error = state.update(|last| Some(last + 1)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::write(&path, "8ZZZZZZZZZZZZZZZZZZZZZZZZZ").unwrap();
+        let error = state.update(|last| Some(last + 1)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str::from_utf8_unchecked,
+};
+
+use crate::base32;
+
+/// A ULID string stored inline, without allocation.
+///
+/// A `UlidString` holds the 26 characters of a ULID in canonical form and is `Copy`.
+/// It dereferences to [`str`], so it can be used wherever a `&str` is expected.
+/// It is returned by [`Ulid::encode()`](crate::Ulid::encode) and [`ZeroableUlid::encode()`](crate::ZeroableUlid::encode).
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::Ulid;
+///
+/// let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+/// let s = u.encode();
+///
+// cspell:disable-next-line
+/// assert_eq!(s, "01JBX69S0YBD54BR1ZV3S8PX92");
+/// assert_eq!(s.len(), 26);
+/// assert!(s.starts_with("01JB"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UlidString {
+    bytes: [u8; 26],
+}
+
+impl UlidString {
+    pub(crate) fn new(n: u128) -> Self {
+        let mut bytes = [0; 26];
+        base32::encode(n, &mut bytes);
+        Self { bytes }
+    }
+
+    /// Returns the ULID string as a `&str`.
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        // Safety: Only created by `base32::encode()`, which guarantees ASCII (and so valid UTF-8)
+        unsafe { from_utf8_unchecked(&self.bytes) }
+    }
+
+    /// Returns the ULID string as ASCII bytes.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; 26] {
+        &self.bytes
+    }
+}
+
+// Hash like `str`, as required by `Borrow<str>`
+impl Hash for UlidString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl Deref for UlidString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for UlidString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for UlidString {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Borrow<str> for UlidString {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for UlidString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for UlidString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<UlidString> for str {
+    fn eq(&self, other: &UlidString) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<UlidString> for &str {
+    fn eq(&self, other: &UlidString) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl fmt::Debug for UlidString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for UlidString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+/// Displays a ULID in lowercase, created by [`Ulid::display_lower()`](crate::Ulid::display_lower)
+/// and [`ZeroableUlid::display_lower()`](crate::ZeroableUlid::display_lower).
+///
+/// Lowercase ULIDs are handy for DNS labels or object storage keys. Width, fill and alignment are honored.
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayLower {
+    ulid: u128,
+}
+
+impl DisplayLower {
+    pub(crate) const fn new(ulid: u128) -> Self {
+        Self { ulid }
+    }
+}
+
+impl fmt::Display for DisplayLower {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = [0; 26];
+        f.pad(base32::encode_lower(self.ulid, &mut buffer))
+    }
+}
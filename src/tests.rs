@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::time::SystemTime;
@@ -82,18 +83,30 @@ fn test_parse() {
 
     assert_eq!(
         "zzzzzzzzzzzzzzzzzzzzzzzzzz".parse::<ZeroableUlid>(),
-        Err(Error::InvalidChar)
+        Err(Error::Overflow { offset: 0, byte: b'z' })
     );
 
-    assert_eq!("".parse::<ZeroableUlid>(), Err(Error::TooShort));
+    assert_eq!(
+        "".parse::<ZeroableUlid>(),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 0
+        })
+    );
 
     assert_eq!(
         "1234567890123456789012345".parse::<ZeroableUlid>(),
-        Err(Error::TooShort)
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 25
+        })
     );
     assert_eq!(
         "123456789012345678901234567".parse::<ZeroableUlid>(),
-        Err(Error::TooLong)
+        Err(Error::TooLong {
+            expected: 26,
+            actual: 27
+        })
     );
 }
 
@@ -138,7 +151,10 @@ fn test_max() {
 
     assert_eq!("7zzzzzzzzzzzzzzzzzzzzzzzzz".parse::<Ulid>(), Ok(ulid_max));
 
-    assert_eq!("80000000000000000000000000".parse::<Ulid>(), Err(Error::InvalidChar));
+    assert_eq!(
+        "80000000000000000000000000".parse::<Ulid>(),
+        Err(Error::Overflow { offset: 0, byte: b'8' })
+    );
 }
 
 #[test]
@@ -193,13 +209,899 @@ fn test_canonicalize() {
         canonicalize("7zzzzzzzzzzzzzzzzzzzzzzzzz"),
         Ok("7ZZZZZZZZZZZZZZZZZZZZZZZZZ".into())
     );
-    assert_eq!(canonicalize("80000000000000000000000000"), Err(Error::InvalidChar));
-    assert_eq!(canonicalize("zzzzzzzzzzzzzzzzzzzzzzzzzz"), Err(Error::InvalidChar));
+    assert_eq!(
+        canonicalize("80000000000000000000000000"),
+        Err(Error::Overflow { offset: 0, byte: b'8' })
+    );
+    assert_eq!(
+        canonicalize("zzzzzzzzzzzzzzzzzzzzzzzzzz"),
+        Err(Error::Overflow { offset: 0, byte: b'z' })
+    );
+    assert_eq!(
+        canonicalize("0000000000000000000000000U"),
+        Err(Error::InvalidChar { offset: 25, byte: b'U' })
+    );
+    assert_eq!(
+        canonicalize("$0000000000000000000000000"),
+        Err(Error::InvalidChar { offset: 0, byte: b'$' })
+    );
+
+    assert_eq!(
+        canonicalize(""),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 0
+        })
+    );
+
+    assert_eq!(
+        canonicalize("1234567890123456789012345"),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 25
+        })
+    );
+    assert_eq!(
+        canonicalize("123456789012345678901234567"),
+        Err(Error::TooLong {
+            expected: 26,
+            actual: 27
+        })
+    );
+}
+
+#[test]
+fn test_parse_with() {
+    let s = "01JB5C84ZBM8QVBE5QRZW6HY89";
+
+    assert!(matches!(
+        canonicalize_with(s, ParseOptions::STRICT),
+        Ok(Cow::Borrowed(_))
+    ));
+    assert_eq!(
+        canonicalize_with(" 01jb5c84zbm8qvbe5qrzw6hy89 ", ParseOptions::LENIENT),
+        Ok(Cow::Owned(s.to_string()))
+    );
+
+    assert_eq!(validate_with(&s.to_lowercase(), ParseOptions::DEFAULT), Ok(()));
+    assert_eq!(
+        validate_with(&s.to_lowercase(), ParseOptions::STRICT),
+        Err(Error::NonCanonicalChar { offset: 2, byte: b'j' })
+    );
+
+    let zero = "urn:ulid:00000000000000000000000000";
+    assert_eq!(Ulid::parse_with(zero, ParseOptions::LENIENT), Err(Error::InvalidZero));
+    assert_eq!(
+        ZeroableUlid::parse_with(zero, ParseOptions::LENIENT),
+        Ok(ZeroableUlid::zeroed())
+    );
+    assert_eq!(Ulid::parse_with(s, ParseOptions::DEFAULT), s.parse());
+}
+
+#[test]
+fn test_encode() {
+    let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    // cspell:disable-next-line
+    let s = "01JBX69S0YBD54BR1ZV3S8PX92";
+
+    let encoded = u.encode();
+    assert_eq!(encoded, s);
+    assert_eq!(s, encoded);
+    assert_eq!(encoded.as_str(), u.to_string());
+    assert_eq!(encoded.as_bytes(), s.as_bytes());
+    assert_eq!(format!("{encoded:?}"), format!("{s:?}"));
+    assert_eq!(format!("{encoded:>28}"), format!("  {s}"));
+    assert_eq!(u.to_zeroable_ulid().encode(), encoded);
+
+    let mut hasher1 = DefaultHasher::new();
+    let mut hasher2 = DefaultHasher::new();
+    encoded.hash(&mut hasher1);
+    s.hash(&mut hasher2);
+    assert_eq!(hasher1.finish(), hasher2.finish());
+
+    let mut buffer = [0; 26];
+    assert_eq!(u.encode_into(&mut buffer), s);
+    assert_eq!(&buffer, s.as_bytes());
+
+    assert_eq!(Ulid::from_ascii(&buffer), Ok(u));
+    assert_eq!(ZeroableUlid::from_ascii(&buffer), Ok(u.to_zeroable_ulid()));
+    assert_eq!(Ulid::from_ascii(&[b'0'; 26]), Err(Error::InvalidZero));
+    assert_eq!(ZeroableUlid::from_ascii(&[b'0'; 26]), Ok(ZeroableUlid::zeroed()));
+    assert_eq!(
+        Ulid::from_ascii(&[b'U'; 26]),
+        Err(Error::InvalidChar { offset: 0, byte: b'U' })
+    );
+}
+
+#[test]
+fn test_display_flags() {
+    let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    let z = u.to_zeroable_ulid();
+    // cspell:disable-next-line
+    let (upper, lower) = ("01JBX69S0YBD54BR1ZV3S8PX92", "01jbx69s0ybd54br1zv3s8px92");
+
+    assert_eq!(format!("{u}"), upper);
+    assert_eq!(format!("{u:#}"), lower);
+    assert_eq!(format!("{z:#}"), lower);
+    assert_eq!(u.display_lower().to_string(), lower);
+    assert_eq!(z.display_lower().to_string(), lower);
+
+    assert_eq!(format!("[{u:>28}]"), format!("[  {upper}]"));
+    assert_eq!(format!("[{u:<28}]"), format!("[{upper}  ]"));
+    assert_eq!(format!("[{u:*^30}]"), format!("[**{upper}**]"));
+    assert_eq!(format!("[{u:>#28}]"), format!("[  {lower}]"));
+    assert_eq!(format!("[{:>28}]", u.display_lower()), format!("[  {lower}]"));
+    assert_eq!(format!("[{z:10}]"), format!("[{upper}]"));
+    assert_eq!(format!("[{u:.4}]"), "[01JB]");
+
+    assert_eq!(format!("{u:x}"), "192fa64e41e5b4a45e03fd8f28b7522");
+    assert_eq!(format!("{u:#034X}"), "0x0192FA64E41E5B4A45E03FD8F28B7522");
+    assert_eq!(format!("{z:032x}"), "0192fa64e41e5b4a45e03fd8f28b7522");
+    assert_eq!(format!("{:b}", Ulid::from_u128(5).unwrap()), "101");
+    assert_eq!(format!("{:#010b}", ZeroableUlid::zeroed()), "0b00000000");
+}
+
+#[test]
+fn test_uuid_string() {
+    let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    let s = "0192fa64-e41e-5b4a-45e0-3fd8f28b7522";
+
+    assert_eq!(u.to_uuid_string(), s);
+    assert_eq!(u.to_zeroable_ulid().to_uuid_string(), s);
+    assert_eq!(Ulid::from_uuid_str(s), Ok(u));
+    assert_eq!(Ulid::from_uuid_str(&s.to_uppercase()), Ok(u));
+    assert_eq!(ZeroableUlid::from_uuid_str(s), Ok(u.to_zeroable_ulid()));
+
+    assert_eq!(Ulid::MAX.to_uuid_string(), "ffffffff-ffff-ffff-ffff-ffffffffffff");
+    assert_eq!(
+        ZeroableUlid::zeroed().to_uuid_string(),
+        "00000000-0000-0000-0000-000000000000"
+    );
+    assert_eq!(
+        Ulid::from_uuid_str("00000000-0000-0000-0000-000000000000"),
+        Err(Error::InvalidZero)
+    );
+    assert_eq!(
+        ZeroableUlid::from_uuid_str("00000000-0000-0000-0000-000000000000"),
+        Ok(ZeroableUlid::zeroed())
+    );
+
+    for _ in 0..100 {
+        let u = Ulid::new();
+        assert_eq!(Ulid::from_uuid_str(&u.to_uuid_string()), Ok(u));
+        assert_eq!(u.to_uuid_string().replace('-', ""), format!("{u:032x}"));
+    }
+
+    assert_eq!(
+        Ulid::from_uuid_str("0192fa64e41e5b4a45e03fd8f28b7522"),
+        Err(Error::TooShort {
+            expected: 36,
+            actual: 32
+        })
+    );
+    assert_eq!(
+        Ulid::from_uuid_str("0192fa64-e41e-5b4a-45e0-3fd8f28b75g2"),
+        Err(Error::InvalidChar { offset: 34, byte: b'g' })
+    );
+    assert_eq!(
+        Ulid::from_uuid_str("0192fa64-e41e-5b4a-45e0_3fd8f28b7522"),
+        Err(Error::InvalidChar { offset: 23, byte: b'_' })
+    );
+    assert_eq!(
+        Ulid::from_uuid_str("0192fa64-e41e-5b4a-45e03-fd8f28b7522"),
+        Err(Error::InvalidChar { offset: 23, byte: b'3' })
+    );
+}
+
+#[test]
+fn test_uuid_v7() {
+    let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    let uuid = u.to_uuid_v7();
+
+    assert_eq!(uuid, 0x0192_FA64_E41E_75B4_85E0_3FD8_F28B_7522);
+    assert!(!u.is_uuid_v7_lossless());
+    assert_eq!(
+        Ulid::from_uuid_v7(uuid).unwrap().to_u128(),
+        0x0192_FA64_E41E_5B40_05E0_3FD8_F28B_7522
+    );
+    assert_eq!(Ulid::from_uuid_v7(uuid).unwrap().to_uuid_v7(), uuid);
+    assert_eq!(u.to_zeroable_ulid().to_uuid_v7(), uuid);
+    assert_eq!(
+        ZeroableUlid::from_uuid_v7(uuid),
+        Ok(Ulid::from_uuid_v7(uuid).unwrap().to_zeroable_ulid())
+    );
+
+    let lossless = Ulid::from_u128(0x0192_FA64_E41E_5B40_05E0_3FD8_F28B_7522).unwrap();
+    assert!(lossless.is_uuid_v7_lossless());
+    assert_eq!(Ulid::from_uuid_v7(lossless.to_uuid_v7()), Ok(lossless));
+
+    for _ in 0..1000 {
+        let u = Ulid::new();
+        let uuid = u.to_uuid_v7();
+        assert_eq!(uuid >> 80, u.to_u128() >> 80);
+        assert_eq!((uuid >> 76) & 0xF, 0x7);
+        assert_eq!((uuid >> 62) & 0x3, 0b10);
+
+        let back = Ulid::from_uuid_v7(uuid).unwrap();
+        assert_eq!(back == u, u.is_uuid_v7_lossless());
+        assert_eq!(back.to_u128() ^ u.to_u128(), u.to_u128() & (0x3F << 62));
+    }
+
+    // Time order is kept
+    let ulids: Vec<Ulid> = (0..1000).map(|_| Ulid::new()).collect();
+    assert!(ulids.windows(2).all(|w| w[0].to_uuid_v7() < w[1].to_uuid_v7()));
+
+    assert_eq!(
+        Ulid::from_uuid_v7(0x0192_FA64_E41E_45B4_85E0_3FD8_F28B_7522),
+        Err(Error::NotUuidV7)
+    );
+    assert_eq!(
+        Ulid::from_uuid_v7(0x0192_FA64_E41E_75B4_C5E0_3FD8_F28B_7522),
+        Err(Error::NotUuidV7)
+    );
+    assert_eq!(Ulid::from_uuid_v7(0x7 << 76 | 0b10 << 62), Err(Error::InvalidZero));
+    assert_eq!(
+        ZeroableUlid::from_uuid_v7(0x7 << 76 | 0b10 << 62),
+        Ok(ZeroableUlid::zeroed())
+    );
+}
+
+struct User;
+
+impl UlidPrefix for User {
+    const PREFIX: &'static str = "user";
+}
+
+struct UserAccount;
+
+impl UlidPrefix for UserAccount {
+    const PREFIX: &'static str = "user_account";
+}
+
+#[test]
+fn test_prefixed_ulid() {
+    type UserId = PrefixedUlid<User>;
+
+    assert_eq!(size_of::<UserId>(), size_of::<Ulid>());
+    assert_eq!(size_of::<Option<UserId>>(), size_of::<Ulid>());
+
+    let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    let id = UserId::from_ulid(u);
+    // cspell:disable-next-line
+    let s = "user_01JBX69S0YBD54BR1ZV3S8PX92";
+
+    assert_eq!(id.to_string(), s);
+    assert_eq!(format!("{id:?}"), format!("PrefixedUlid({s:?})"));
+    assert_eq!(format!("[{id:>33}]"), format!("[  {s}]"));
+    assert_eq!(id.ulid(), u);
+    assert_eq!(UserId::prefix(), "user");
+    assert_eq!(Ulid::from(id), u);
+    assert_eq!(UserId::from(u), id);
+
+    assert_eq!(s.parse(), Ok(id));
+    assert_eq!(s.to_lowercase().parse(), Ok(id));
+    assert_eq!(
+        PrefixedUlid::<UserAccount>::from_ulid(u).to_string(),
+        format!("user_account_{u}")
+    );
+    assert_eq!(
+        format!("user_account_{u}").parse(),
+        Ok(PrefixedUlid::<UserAccount>::from_ulid(u))
+    );
+
+    let wrong_prefix = Err(PrefixedUlidError::WrongPrefix { expected: "user" });
+    assert_eq!(format!("order_{u}").parse::<UserId>(), wrong_prefix);
+    assert_eq!(format!("user_account_{u}").parse::<UserId>(), wrong_prefix);
+    assert_eq!(format!("user{u}").parse::<UserId>(), wrong_prefix);
+    assert_eq!(u.to_string().parse::<UserId>(), wrong_prefix);
+    assert_eq!(
+        format!("{u}")
+            .parse::<PrefixedUlid<UserAccount>>()
+            .unwrap_err()
+            .to_string(),
+        "wrong prefix: expected 'user_account_'"
+    );
+
+    let error = "user_01JBX69S0YBD54BR1ZV3S8PX9U".parse::<UserId>().unwrap_err();
+    assert_eq!(
+        error,
+        PrefixedUlidError::InvalidUlid(Error::InvalidChar { offset: 30, byte: b'U' })
+    );
+    assert_eq!(error.to_string(), "invalid ULID: invalid character 'U' at offset 30");
+    assert_eq!(
+        "user_00000000000000000000000000".parse::<UserId>(),
+        Err(PrefixedUlidError::InvalidUlid(Error::InvalidZero))
+    );
+
+    // Underscores within the ULID are invalid characters, not part of the prefix
+    let invalid_underscore = |offset| {
+        Err(PrefixedUlidError::InvalidUlid(Error::InvalidChar {
+            offset,
+            byte: b'_',
+        }))
+    };
+    // cspell:disable-next-line
+    assert_eq!(
+        "user_01JBX_69S0YBD54BR1ZV3S8PX9".parse::<UserId>(),
+        invalid_underscore(10)
+    );
+    assert_eq!(format!("{s}_").parse::<UserId>(), invalid_underscore(31));
+    assert_eq!(format!("{s}_").to_lowercase().parse::<UserId>(), invalid_underscore(31));
+    assert_eq!("user__".parse::<UserId>(), invalid_underscore(5));
+    assert_eq!(
+        format!("user_account_{u}_").parse::<PrefixedUlid<UserAccount>>(),
+        Err(PrefixedUlidError::InvalidUlid(Error::InvalidChar {
+            offset: 39,
+            byte: b'_'
+        }))
+    );
+
+    let ids: Vec<UserId> = (0..10).map(|_| UserId::new()).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_prefixed_ulid_length_errors() {
+    type UserId = PrefixedUlid<User>;
+    // cspell:disable-next-line
+    let s = "user_01JBX69S0YBD54BR1ZV3S8PX92";
+
+    // Lengths count the prefix too, so the caret points into the whole input
+    let error = format!("user_{}", &s[5..30]).parse::<UserId>().unwrap_err();
+    assert_eq!(
+        error,
+        PrefixedUlidError::InvalidUlid(Error::TooShort {
+            expected: 31,
+            actual: 30
+        })
+    );
+    // cspell:disable-next-line
+    let input = "user_01JB5C84ZB";
+    let PrefixedUlidError::InvalidUlid(error) = input.parse::<UserId>().unwrap_err() else {
+        panic!("expected an invalid ULID");
+    };
+    assert_eq!(error.offset(), Some(15));
+    assert_eq!(
+        error.with_input(input).to_string(),
+        "input is too short: expected 31 bytes, found 15\nuser_01JB5C84ZB\n               ^"
+    );
+    let input = format!("{s}XX");
+    let PrefixedUlidError::InvalidUlid(error) = input.parse::<UserId>().unwrap_err() else {
+        panic!("expected an invalid ULID");
+    };
+    assert_eq!(
+        error,
+        Error::TooLong {
+            expected: 31,
+            actual: 33
+        }
+    );
+    assert_eq!(
+        error.with_input(&input).to_string(),
+        format!(
+            "input is too long: expected 31 bytes, found 33\n{input}\n{}^",
+            " ".repeat(31)
+        )
+    );
+}
+
+// cspell:disable
+/// Valid test vectors of the TypeID specification: name, TypeID, prefix, and UUID.
+const TYPE_ID_VALID: [(&str, &str, &str, u128); 9] = [
+    ("nil", "00000000000000000000000000", "", 0),
+    ("one", "00000000000000000000000001", "", 1),
+    ("ten", "0000000000000000000000000a", "", 10),
+    ("sixteen", "0000000000000000000000000g", "", 16),
+    ("thirty-two", "00000000000000000000000010", "", 32),
+    ("max-valid", "7zzzzzzzzzzzzzzzzzzzzzzzzz", "", u128::MAX),
+    (
+        "valid-alphabet",
+        "prefix_0123456789abcdefghjkmnpqrs",
+        "prefix",
+        0x0110_C853_1D09_52D8_D73E_1194_E95B_5F19,
+    ),
+    (
+        "valid-uuidv7",
+        "prefix_01h455vb4pex5vsknk084sn02q",
+        "prefix",
+        0x0189_0A5D_AC96_774B_BCCE_B302_099A_8057,
+    ),
+    ("prefix-underscore", "pre_fix_00000000000000000000000000", "pre_fix", 0),
+];
+
+/// Invalid test vectors of the TypeID specification: name and TypeID.
+const TYPE_ID_INVALID: [(&str, &str); 19] = [
+    ("prefix-uppercase", "PREFIX_00000000000000000000000000"),
+    ("prefix-numeric", "12345_00000000000000000000000000"),
+    ("prefix-period", "pre.fix_00000000000000000000000000"),
+    ("prefix-non-ascii", "préfix_00000000000000000000000000"),
+    ("prefix-spaces", "  prefix_00000000000000000000000000"),
+    (
+        "prefix-64-chars",
+        "abcdefghijklmnopqrstuvwxyzabcdefghijklmnopqrstuvwxyzabcdefghijkl_00000000000000000000000000",
+    ),
+    ("separator-empty-prefix", "_00000000000000000000000000"),
+    ("separator-empty", "_"),
+    ("suffix-short", "prefix_1234567890123456789012345"),
+    ("suffix-long", "prefix_123456789012345678901234567"),
+    ("suffix-spaces", "prefix_1234567890123456789012345 "),
+    ("suffix-uppercase", "prefix_0123456789ABCDEFGHJKMNPQRS"),
+    ("suffix-hyphens", "prefix_123456789-123456789-123456"),
+    ("suffix-wrong-alphabet", "prefix_ooooooiiiiiiuuuuuuulllllll"),
+    ("suffix-ambiguous-crockford", "prefix_i23456789ooqrstuvwxyz0123"),
+    ("suffix-overflow", "prefix_8zzzzzzzzzzzzzzzzzzzzzzzzz"),
+    ("prefix-underscore-start", "_prefix_00000000000000000000000000"),
+    ("prefix-underscore-end", "prefix__00000000000000000000000000"),
+    ("empty", ""),
+];
+// cspell:enable
+
+#[test]
+fn test_type_id_spec() {
+    for (name, s, prefix, uuid) in TYPE_ID_VALID {
+        let id: TypeId = s.parse().unwrap_or_else(|error| panic!("{name}: {error}"));
+        assert_eq!(id.prefix(), prefix, "{name}");
+        assert_eq!(id.uuid(), uuid, "{name}");
+        assert_eq!(TypeId::from_uuid(prefix, uuid).unwrap().to_string(), s, "{name}");
+    }
+    for (name, s) in TYPE_ID_INVALID {
+        assert!(s.parse::<TypeId>().is_err(), "{name}");
+    }
+}
+
+#[test]
+fn test_type_id() {
+    let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    let id = TypeId::from_ulid("user", u).unwrap();
+    // cspell:disable-next-line
+    let s = "user_01jbx69s0ybd54br1zv3s8px92";
+
+    assert_eq!(id.to_string(), s);
+    assert_eq!(format!("{id:?}"), format!("TypeId({s:?})"));
+    assert_eq!(format!("[{id:<32}]"), format!("[{s} ]"));
+    assert_eq!(id.to_ulid(), Some(u));
+    assert_eq!(s.parse(), Ok(id));
+    assert_eq!(s[5..].parse::<TypeId>().unwrap().to_ulid(), Some(u));
+    assert_eq!(TypeId::from_uuid("", 0).unwrap().to_ulid(), None);
+
+    assert_eq!(TypeId::from_ulid("User", u), Err(TypeIdError::InvalidPrefix));
+    assert_eq!(TypeId::from_ulid("user_", u), Err(TypeIdError::InvalidPrefix));
+    assert_eq!(
+        "2fa_01jbx69s0ybd54br1zv3s8px92".parse::<TypeId>(),
+        Err(TypeIdError::InvalidPrefix)
+    );
+
+    // ULIDs are accepted in uppercase, TypeIDs are not
+    let error = format!("user_{u}").parse::<TypeId>().unwrap_err();
+    assert_eq!(
+        error,
+        TypeIdError::InvalidSuffix(Error::NonCanonicalChar { offset: 7, byte: b'J' })
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid TypeID suffix: non-canonical character 'J' at offset 7"
+    );
+    assert_eq!(
+        "user_81jbx69s0ybd54br1zv3s8px92".parse::<TypeId>(),
+        Err(TypeIdError::InvalidSuffix(Error::Overflow { offset: 5, byte: b'8' }))
+    );
+    assert_eq!(
+        "user_01jbx69s0ybd54br1zv3s8px9".parse::<TypeId>(),
+        Err(TypeIdError::InvalidSuffix(Error::TooShort {
+            expected: 31,
+            actual: 30
+        }))
+    );
+
+    // Length errors point into the whole input, behind the 26 characters of the suffix
+    // cspell:disable-next-line
+    let input = "user_01h455vb4pex5vsknk084sn02qxx";
+    let Err(TypeIdError::InvalidSuffix(error)) = input.parse::<TypeId>() else {
+        panic!("expected an invalid suffix");
+    };
+    assert_eq!(
+        error,
+        Error::TooLong {
+            expected: 31,
+            actual: 33
+        }
+    );
+    assert_eq!(
+        error.with_input(input).to_string(),
+        format!(
+            "input is too long: expected 31 bytes, found 33\n{input}\n{}^",
+            " ".repeat(31)
+        )
+    );
+
+    // Conversion via UUID v7 gives spec-conforming TypeIDs
+    let id = TypeId::from_uuid("user", u.to_uuid_v7()).unwrap();
+    assert_eq!(id.uuid() >> 76 & 0xF, 7);
+    assert_eq!(Ulid::from_uuid_v7(id.uuid()).unwrap().timestamp(), u.timestamp());
+}
+
+#[test]
+fn test_id() {
+    use std::{collections::HashSet, fmt, rc::Rc, str::FromStr};
+
+    // Neither `Copy`, `Hash`, `Ord`, `Send` nor `Sync`
+    struct Entity(#[allow(dead_code)] Rc<()>);
+
+    fn assert_traits<T: Copy + Ord + Hash + Default + Send + Sync + fmt::Debug + fmt::Display + FromStr>() {}
+    assert_traits::<Id<Entity>>();
+
+    assert_eq!(size_of::<Id<Entity>>(), size_of::<Ulid>());
+    assert_eq!(size_of::<Option<Id<Entity>>>(), size_of::<Ulid>());
+
+    let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    let id = Id::<Entity>::from_ulid(u);
+
+    assert_eq!(id.ulid(), u);
+    assert_eq!(Ulid::from(id), u);
+    assert_eq!(u128::from(id), u.to_u128());
+    assert_eq!(Id::<Entity>::try_from(u.to_u128()), Ok(id));
+    assert_eq!(Id::<Entity>::try_from(0), Err(Error::InvalidZero));
+
+    assert_eq!(id.to_string(), u.to_string());
+    assert_eq!(format!("{id:#>30}"), format!("{u:#>30}"));
+    assert_eq!(format!("{id:#}"), format!("{u:#}"));
+    assert_eq!(format!("{id:x} {id:X} {id:b}"), format!("{u:x} {u:X} {u:b}"));
+    assert_eq!(format!("{id:?}"), format!("{u:?}").replacen("Ulid", "Id", 1));
+
+    assert_eq!(u.to_string().parse(), Ok(id));
+    assert_eq!(
+        "0".parse::<Id<Entity>>(),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 1
+        })
+    );
+
+    let ids: Vec<Id<Entity>> = (0..10).map(|_| Id::new()).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 10);
+}
+
+#[test]
+fn test_compact_encodings() {
+    let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+    let z = ZeroableUlid::from(u);
+
+    // cspell:disable
+    assert_eq!(u.to_base58_string(), "1CGu1BjU9Rr9h2ZVm6P3oo");
+    assert_eq!(u.to_base62_string(), "02yA0piThDHxaCD6tFIMHi");
+    assert_eq!(u.to_base64url_string(), "AZL6ZOQeW0pF4D_Y8ot1Ig");
+    assert_eq!(Ulid::from_base58_str("1CGu1BjU9Rr9h2ZVm6P3oo"), Ok(u));
+    assert_eq!(Ulid::from_base62_str("02yA0piThDHxaCD6tFIMHi"), Ok(u));
+    assert_eq!(Ulid::from_base64url_str("AZL6ZOQeW0pF4D_Y8ot1Ig"), Ok(u));
+    assert_eq!(z.to_base62_string(), u.to_base62_string());
+
+    let zero = ZeroableUlid::zeroed();
+    assert_eq!(zero.to_base58_string(), "1".repeat(22));
+    assert_eq!(zero.to_base62_string(), "0".repeat(22));
+    assert_eq!(zero.to_base64url_string(), "A".repeat(22));
+    assert_eq!(ZeroableUlid::from_base58_str(&"1".repeat(22)), Ok(zero));
+    assert_eq!(Ulid::from_base58_str(&"1".repeat(22)), Err(Error::InvalidZero));
+    assert_eq!(Ulid::from_base62_str(&"0".repeat(22)), Err(Error::InvalidZero));
+    assert_eq!(Ulid::from_base64url_str(&"A".repeat(22)), Err(Error::InvalidZero));
+
+    let max = ZeroableUlid::from_u128(u128::MAX);
+    assert_eq!(max.to_base58_string(), "YcVfxkQb6JRzqk5kF2tNLv");
+    assert_eq!(max.to_base62_string(), "7n42DGM5Tflk9n8mt7Fhc7");
+    assert_eq!(max.to_base64url_string(), "_____________________w");
+    let too_large = Err(Error::ValueTooLarge);
+    assert_eq!(ZeroableUlid::from_base58_str("YcVfxkQb6JRzqk5kF2tNLw"), too_large);
+    assert_eq!(ZeroableUlid::from_base62_str("7n42DGM5Tflk9n8mt7Fhc8"), too_large);
+    assert_eq!(ZeroableUlid::from_base62_str(&"z".repeat(22)), too_large);
+
+    // Non-zero bits after the 128 bits
+    assert_eq!(
+        Ulid::from_base64url_str("AZL6ZOQeW0pF4D_Y8ot1Ih"),
+        Err(Error::InvalidChar { offset: 21, byte: b'h' })
+    );
+    assert_eq!(
+        Ulid::from_base58_str("1CGu1BjU9Rr9h2ZVm6P3o0"),
+        Err(Error::InvalidChar { offset: 21, byte: b'0' })
+    );
+    assert_eq!(
+        Ulid::from_base62_str("02yA0piThDHxaCD6tFIMH-"),
+        Err(Error::InvalidChar { offset: 21, byte: b'-' })
+    );
+    assert_eq!(
+        Ulid::from_base64url_str("AZL6ZOQeW0pF4D+Y8ot1Ig"),
+        Err(Error::InvalidChar { offset: 14, byte: b'+' })
+    );
+    // cspell:enable
+
+    for from_str in [Ulid::from_base58_str, Ulid::from_base62_str, Ulid::from_base64url_str] {
+        assert_eq!(
+            from_str(&"1".repeat(21)),
+            Err(Error::TooShort {
+                expected: 22,
+                actual: 21
+            })
+        );
+        assert_eq!(
+            from_str(&"1".repeat(23)),
+            Err(Error::TooLong {
+                expected: 22,
+                actual: 23
+            })
+        );
+        assert_eq!(
+            from_str(&u.to_string()),
+            Err(Error::TooLong {
+                expected: 22,
+                actual: 26
+            })
+        );
+    }
+
+    let mut ulids: Vec<Ulid> = (0..1000).map(|_| Ulid::new()).collect();
+    ulids.extend([u, Ulid::MIN, Ulid::MAX]);
+    ulids.sort();
+    for u in &ulids {
+        assert_eq!(Ulid::from_base58_str(&u.to_base58_string()), Ok(*u));
+        assert_eq!(Ulid::from_base62_str(&u.to_base62_string()), Ok(*u));
+        assert_eq!(Ulid::from_base64url_str(&u.to_base64url_string()), Ok(*u));
+    }
+    assert!(
+        ulids
+            .windows(2)
+            .all(|w| w[0].to_base58_string() < w[1].to_base58_string())
+    );
+    assert!(
+        ulids
+            .windows(2)
+            .all(|w| w[0].to_base62_string() < w[1].to_base62_string())
+    );
+}
+
+#[test]
+fn test_human() {
+    let u = Ulid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522).unwrap();
+
+    // cspell:disable
+    assert_eq!(u.to_human_string(), "01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2D");
+    assert_eq!(Ulid::from_human_str("01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2D"), Ok(u));
+    assert_eq!(Ulid::from_human_str("01jbx-69s0y-bd54b-r1zv3-s8px9-2d"), Ok(u));
+    assert_eq!(Ulid::from_human_str("01JBX69S0YBD54BR1ZV3S8PX92D"), Ok(u));
+    assert_eq!(Ulid::from_human_str("01JBX69S0Y-BD54BR1ZV3S8PX92-D"), Ok(u));
+    assert_eq!(
+        ZeroableUlid::from_human_str("01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2D"),
+        Ok(u.into())
+    );
+
+    let z = ZeroableUlid::from_u128(36);
+    assert_eq!(z.to_human_string(), "00000-00000-00000-00000-00001-4U");
+    assert_eq!(ZeroableUlid::from_human_str("00000-00000-00000-00000-00001-4u"), Ok(z));
+    assert_eq!(Ulid::from_human_str(&"0".repeat(27)), Err(Error::InvalidZero));
+
+    // A unique correction between easily confused characters
+    let input = "01JBX-69S0Y-BD54V-R1ZV3-S8PX9-2D";
+    let error = Ulid::from_human_str(input).unwrap_err();
+    let typo = Typo::new(16, b'V', b'B');
+    assert_eq!(error, Error::ChecksumMismatch { typo: Some(typo) });
+    assert_eq!(error.offset(), Some(16));
+    assert_eq!(
+        error.to_string(),
+        "checksum mismatch: likely typo 'V' at offset 16, expected 'B'"
+    );
+    assert_eq!(
+        error.with_input(input).to_string(),
+        "checksum mismatch: likely typo 'V' at offset 16, expected 'B'\n01JBX-69S0Y-BD54V-R1ZV3-S8PX9-2D\n                ^"
+    );
+    assert_eq!(typo_candidates(input).collect::<Vec<_>>(), [typo]);
+
+    // Ambiguous corrections
+    let input = "01JBX-69SQY-BD54B-R1ZV3-S8PX9-2D";
+    let error = Ulid::from_human_str(input).unwrap_err();
+    assert_eq!(error, Error::ChecksumMismatch { typo: None });
+    assert_eq!(error.offset(), None);
+    assert_eq!(error.to_string(), "checksum mismatch");
+    assert_eq!(
+        typo_candidates(input).collect::<Vec<_>>(),
+        [
+            Typo::new(3, b'B', b'8'),
+            Typo::new(9, b'Q', b'0'),
+            Typo::new(25, b'8', b'B')
+        ]
+    );
+
+    // Typo in the check symbol
+    let input = "01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2p";
+    assert!(matches!(
+        Ulid::from_human_str(input),
+        Err(Error::ChecksumMismatch { .. })
+    ));
+    assert!(typo_candidates(input).any(|typo| typo == Typo::new(31, b'p', b'D')));
+
+    assert_eq!(typo_candidates("01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2D").count(), 0);
+    assert_eq!(typo_candidates("01JBX").count(), 0);
+
+    assert_eq!(
+        Ulid::from_human_str("01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2#"),
+        Err(Error::InvalidChar { offset: 31, byte: b'#' })
+    );
+    assert_eq!(
+        Ulid::from_human_str("01JBX-69S0Y-BD54B-R1ZV3-S8PX9-*D"),
+        Err(Error::InvalidChar { offset: 30, byte: b'*' })
+    );
+    // cspell:enable
+    assert_eq!(
+        Ulid::from_human_str(""),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 0
+        })
+    );
 
-    assert_eq!(canonicalize(""), Err(Error::TooShort));
+    // Every single mistyped character is detected
+    let s = u.to_human_string();
+    for (offset, found) in s.bytes().enumerate().filter(|&(_, byte)| byte != b'-') {
+        for byte in b"0123456789ABCDEFGHJKMNPQRSTVWXYZ*~$=U"
+            .iter()
+            .filter(|&&byte| byte != found)
+        {
+            let mut typo = s.clone().into_bytes();
+            typo[offset] = *byte;
+            let typo = String::from_utf8(typo).unwrap();
+            assert!(Ulid::from_human_str(&typo).is_err(), "{typo}");
+        }
+    }
+}
+
+#[test]
+fn test_encode_decode_many() {
+    let ulids: Vec<Ulid> = (0..100).map(|_| Ulid::new()).collect();
+
+    let mut buffer = vec![0; 26 * ulids.len() + 10];
+    let encoded = encode_many(&ulids, &mut buffer);
+    assert_eq!(encoded.len(), 26 * ulids.len());
+    assert_eq!(&encoded[26..52], ulids[1].to_string());
+
+    let mut decoded = vec![Ulid::MIN; ulids.len()];
+    assert_eq!(decode_many(encoded.as_bytes(), &mut decoded), Ok(()));
+    assert_eq!(decoded, ulids);
+
+    let mut invalid = encoded.as_bytes().to_vec();
+    invalid[26 * 42 + 5] = b'!';
+    invalid[26 * 50] = b'8';
+    assert_eq!(
+        decode_many(&invalid, &mut decoded),
+        Err(BatchError::new(42, Error::InvalidChar { offset: 5, byte: b'!' }))
+    );
+
+    assert_eq!(
+        decode_many(&[b'0'; 26], &mut decoded[..1]).unwrap_err().error(),
+        Error::InvalidZero
+    );
+    assert_eq!(decode_many(&[], &mut []), Ok(()));
+}
+
+#[test]
+fn test_decode_many_blocks() {
+    let ulids: Vec<Ulid> = (0..200).map(|_| Ulid::new()).collect();
+    let mut buffer = vec![0; 26 * ulids.len()];
+    let encoded = encode_many(&ulids, &mut buffer).as_bytes().to_vec();
+
+    // Errors in later blocks, and entries before them decoded
+    for index in [0, 63, 64, 130, 199] {
+        let mut invalid = encoded.clone();
+        invalid[26 * index + 25] = b'U';
+        let mut decoded = vec![Ulid::MIN; ulids.len()];
+        assert_eq!(
+            decode_many(&invalid, &mut decoded),
+            Err(BatchError::new(index, Error::InvalidChar { offset: 25, byte: b'U' }))
+        );
+        assert_eq!(decoded[..index], ulids[..index]);
+        assert!(decoded[index..].iter().all(|&ulid| ulid == Ulid::MIN));
+    }
 
-    assert_eq!(canonicalize("1234567890123456789012345"), Err(Error::TooShort));
-    assert_eq!(canonicalize("123456789012345678901234567"), Err(Error::TooLong));
+    let mut zero = encoded;
+    zero[26 * 100..26 * 101].fill(b'0');
+    let mut decoded = vec![Ulid::MIN; ulids.len()];
+    assert_eq!(
+        decode_many(&zero, &mut decoded),
+        Err(BatchError::new(100, Error::InvalidZero))
+    );
+    assert_eq!(decoded[..100], ulids[..100]);
+}
+
+#[test]
+fn test_decode_many_length_mismatch() {
+    let mut decoded = [Ulid::MIN; 3];
+
+    assert_eq!(
+        decode_many(&[b'1'; 26 * 2 + 5], &mut decoded),
+        Err(BatchError::new(
+            2,
+            Error::TooShort {
+                expected: 26,
+                actual: 5
+            }
+        ))
+    );
+    assert_eq!(
+        decode_many(&[b'1'; 26 * 2], &mut decoded),
+        Err(BatchError::new(
+            2,
+            Error::TooShort {
+                expected: 26,
+                actual: 0
+            }
+        ))
+    );
+    assert_eq!(
+        decode_many(&[b'1'; 26 * 3 + 1], &mut decoded),
+        Err(BatchError::new(
+            2,
+            Error::TooLong {
+                expected: 26,
+                actual: 27
+            }
+        ))
+    );
+    assert_eq!(
+        decode_many(&[b'1'; 1], &mut []),
+        Err(BatchError::new(0, Error::TooLong { expected: 0, actual: 1 }))
+    );
+
+    // Nothing is decoded on a length mismatch
+    assert_eq!(decoded, [Ulid::MIN; 3]);
+}
+
+#[test]
+fn test_encode_decode_separated() {
+    let ulids: Vec<Ulid> = (0..100).map(|_| Ulid::new()).collect();
+
+    let lines = encode_separated(&ulids, b'\n');
+    assert_eq!(lines.len(), 27 * ulids.len() - 1);
+    assert_eq!(lines.lines().nth(7), Some(ulids[7].to_string().as_str()));
+
+    assert_eq!(decode_separated(lines.as_bytes(), b'\n'), Ok(ulids.clone()));
+    assert_eq!(
+        decode_separated(format!("{lines}\n").as_bytes(), b'\n'),
+        Ok(ulids.clone())
+    );
+    assert_eq!(
+        decode_separated(lines.replace('\n', "\r\n").as_bytes(), b'\n'),
+        Ok(ulids.clone())
+    );
+
+    let csv = encode_separated(&ulids, b',');
+    assert_eq!(decode_separated(csv.as_bytes(), b','), Ok(ulids));
+
+    assert_eq!(encode_separated(&[], b','), "");
+    assert_eq!(decode_separated(b"", b','), Ok(vec![]));
+    assert_eq!(decode_separated(b"\n", b'\n'), Ok(vec![]));
+
+    let error = decode_separated(format!("{csv},,").as_bytes(), b',').unwrap_err();
+    assert_eq!(
+        error,
+        BatchError::new(
+            100,
+            Error::TooShort {
+                expected: 26,
+                actual: 0
+            }
+        )
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid entry 100: input is too short: expected 26 bytes, found 0"
+    );
+
+    // cspell:disable-next-line
+    let error = decode_separated(b"01JB5C84ZBM8QVBE5QRZW6HY89, 01JB5C84ZBM8QVBE5QRZW6HY89", b',').unwrap_err();
+    assert_eq!(error.index(), 1);
+    assert_eq!(
+        error.error(),
+        Error::TooLong {
+            expected: 26,
+            actual: 27
+        }
+    );
 }
 
 #[test]
@@ -216,13 +1118,45 @@ fn test_validate() {
 
     assert!(validate("7zzzzzzzzzzzzzzzzzzzzzzzzz").is_ok(),);
 
-    assert_eq!(validate("80000000000000000000000000"), Err(Error::InvalidChar));
-    assert_eq!(validate("zzzzzzzzzzzzzzzzzzzzzzzzzz"), Err(Error::InvalidChar));
+    assert_eq!(
+        validate("80000000000000000000000000"),
+        Err(Error::Overflow { offset: 0, byte: b'8' })
+    );
+    assert_eq!(
+        validate("zzzzzzzzzzzzzzzzzzzzzzzzzz"),
+        Err(Error::Overflow { offset: 0, byte: b'z' })
+    );
+    assert_eq!(
+        validate("0000000000000000000000000U"),
+        Err(Error::InvalidChar { offset: 25, byte: b'U' })
+    );
+    assert_eq!(
+        validate("$0000000000000000000000000"),
+        Err(Error::InvalidChar { offset: 0, byte: b'$' })
+    );
 
-    assert_eq!(validate(""), Err(Error::TooShort));
+    assert_eq!(
+        validate(""),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 0
+        })
+    );
 
-    assert_eq!(validate("1234567890123456789012345"), Err(Error::TooShort));
-    assert_eq!(validate("123456789012345678901234567"), Err(Error::TooLong));
+    assert_eq!(
+        validate("1234567890123456789012345"),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 25
+        })
+    );
+    assert_eq!(
+        validate("123456789012345678901234567"),
+        Err(Error::TooLong {
+            expected: 26,
+            actual: 27
+        })
+    );
 }
 
 // --- From/TryFrom trait tests for Ulid ---
@@ -341,15 +1275,33 @@ fn test_ulid_try_from_byte_slice() {
 
     // Too short
     let short: &[u8] = &[1, 2, 3];
-    assert_eq!(Ulid::try_from(short), Err(Error::TooShort));
+    assert_eq!(
+        Ulid::try_from(short),
+        Err(Error::TooShort {
+            expected: 16,
+            actual: 3
+        })
+    );
 
     // Too long
     let long: &[u8] = &[0; 17];
-    assert_eq!(Ulid::try_from(long), Err(Error::TooLong));
+    assert_eq!(
+        Ulid::try_from(long),
+        Err(Error::TooLong {
+            expected: 16,
+            actual: 17
+        })
+    );
 
     // Empty slice
     let empty: &[u8] = &[];
-    assert_eq!(Ulid::try_from(empty), Err(Error::TooShort));
+    assert_eq!(
+        Ulid::try_from(empty),
+        Err(Error::TooShort {
+            expected: 16,
+            actual: 0
+        })
+    );
 }
 
 // --- From/TryFrom trait tests for ZeroableUlid ---
@@ -457,15 +1409,33 @@ fn test_zeroable_ulid_try_from_byte_slice() {
 
     // Too short
     let short: &[u8] = &[1, 2, 3];
-    assert_eq!(ZeroableUlid::try_from(short), Err(Error::TooShort));
+    assert_eq!(
+        ZeroableUlid::try_from(short),
+        Err(Error::TooShort {
+            expected: 16,
+            actual: 3
+        })
+    );
 
     // Too long
     let long: &[u8] = &[0; 17];
-    assert_eq!(ZeroableUlid::try_from(long), Err(Error::TooLong));
+    assert_eq!(
+        ZeroableUlid::try_from(long),
+        Err(Error::TooLong {
+            expected: 16,
+            actual: 17
+        })
+    );
 
     // Empty slice
     let empty: &[u8] = &[];
-    assert_eq!(ZeroableUlid::try_from(empty), Err(Error::TooShort));
+    assert_eq!(
+        ZeroableUlid::try_from(empty),
+        Err(Error::TooShort {
+            expected: 16,
+            actual: 0
+        })
+    );
 }
 
 // --- Constants ---
@@ -640,18 +1610,65 @@ fn test_ulid_parse_and_display() {
     assert_eq!("00000000000000000000000000".parse::<Ulid>(), Err(Error::InvalidZero));
 
     // Invalid characters
-    assert_eq!("0000000000000000000000000U".parse::<Ulid>(), Err(Error::InvalidChar));
-    assert_eq!("0000000000000000000000000u".parse::<Ulid>(), Err(Error::InvalidChar));
-    assert_eq!("000000000000000000000000$$".parse::<Ulid>(), Err(Error::InvalidChar));
+    assert_eq!(
+        "0000000000000000000000000U".parse::<Ulid>(),
+        Err(Error::InvalidChar { offset: 25, byte: b'U' })
+    );
+    assert_eq!(
+        "0000000000000000000000000u".parse::<Ulid>(),
+        Err(Error::InvalidChar { offset: 25, byte: b'u' })
+    );
+    assert_eq!(
+        "000000000000000000000000$$".parse::<Ulid>(),
+        Err(Error::InvalidChar { offset: 24, byte: b'$' })
+    );
+    assert_eq!(
+        "0000000000ä000000000000000".parse::<Ulid>(),
+        Err(Error::TooLong {
+            expected: 26,
+            actual: 27
+        })
+    );
+    assert_eq!(
+        "0000000000ä00000000000000".parse::<Ulid>(),
+        Err(Error::InvalidChar { offset: 10, byte: 0xC3 })
+    );
 
     // Overflow (first char > 7)
-    assert_eq!("80000000000000000000000000".parse::<Ulid>(), Err(Error::InvalidChar));
+    assert_eq!(
+        "80000000000000000000000000".parse::<Ulid>(),
+        Err(Error::Overflow { offset: 0, byte: b'8' })
+    );
 
     // Wrong length
-    assert_eq!("".parse::<Ulid>(), Err(Error::TooShort));
-    assert_eq!("0".parse::<Ulid>(), Err(Error::TooShort));
-    assert_eq!("0000000000000000000000000".parse::<Ulid>(), Err(Error::TooShort));
-    assert_eq!("000000000000000000000000000".parse::<Ulid>(), Err(Error::TooLong));
+    assert_eq!(
+        "".parse::<Ulid>(),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        "0".parse::<Ulid>(),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 1
+        })
+    );
+    assert_eq!(
+        "0000000000000000000000000".parse::<Ulid>(),
+        Err(Error::TooShort {
+            expected: 26,
+            actual: 25
+        })
+    );
+    assert_eq!(
+        "000000000000000000000000000".parse::<Ulid>(),
+        Err(Error::TooLong {
+            expected: 26,
+            actual: 27
+        })
+    );
 }
 
 #[test]
@@ -940,7 +1957,13 @@ fn test_zeroable_ulid_uniques() {
 #[test]
 fn test_error_debug() {
     // Debug should be derivable and not panic
-    let s = format!("{:?}", Error::TooShort);
+    let s = format!(
+        "{:?}",
+        Error::TooShort {
+            expected: 26,
+            actual: 0
+        }
+    );
     assert!(!s.is_empty());
     assert!(s.contains("TooShort"));
 }
@@ -950,7 +1973,7 @@ fn test_error_std_error() {
     // Error implements std::error::Error
     fn accepts_std_error(_: &dyn std::error::Error) {}
 
-    let err = Error::InvalidChar;
+    let err = Error::InvalidChar { offset: 0, byte: b'U' };
     accepts_std_error(&err);
 
     // source() should return None (no underlying cause)
@@ -979,23 +2002,153 @@ fn test_error_hash() {
         hasher.finish()
     }
 
+    let too_short = Error::TooShort {
+        expected: 26,
+        actual: 0,
+    };
+    let too_long = Error::TooLong {
+        expected: 26,
+        actual: 0,
+    };
+
     // Same variant → same hash
-    assert_eq!(hash_of(&Error::TooShort), hash_of(&Error::TooShort));
+    assert_eq!(hash_of(&too_short), hash_of(&too_short));
 
     // Different variants → different hash (probabilistically)
-    assert_ne!(hash_of(&Error::TooShort), hash_of(&Error::TooLong));
+    assert_ne!(hash_of(&too_short), hash_of(&too_long));
 }
 
 #[test]
 fn test_error_eq() {
-    assert_eq!(Error::TooShort, Error::TooShort);
-    assert_ne!(Error::TooShort, Error::TooLong);
-    assert_ne!(Error::InvalidChar, Error::InvalidZero);
+    let too_short = Error::TooShort {
+        expected: 26,
+        actual: 0,
+    };
+    let too_long = Error::TooLong {
+        expected: 26,
+        actual: 0,
+    };
+
+    assert_eq!(too_short, too_short);
+    assert_ne!(too_short, too_long);
+    assert_ne!(
+        too_short,
+        Error::TooShort {
+            expected: 26,
+            actual: 1
+        }
+    );
+    assert_ne!(Error::InvalidChar { offset: 0, byte: b'U' }, Error::InvalidZero);
     assert_ne!(Error::TimestampOutOfRange, Error::RandomnessOutOfRange);
 }
 
+#[test]
+fn test_error_display() {
+    assert_eq!(
+        Error::TooShort {
+            expected: 26,
+            actual: 3
+        }
+        .to_string(),
+        "input is too short: expected 26 bytes, found 3"
+    );
+    assert_eq!(
+        Error::TooLong {
+            expected: 16,
+            actual: 17
+        }
+        .to_string(),
+        "input is too long: expected 16 bytes, found 17"
+    );
+    assert_eq!(
+        Error::InvalidChar { offset: 5, byte: b'U' }.to_string(),
+        "invalid character 'U' at offset 5"
+    );
+    assert_eq!(
+        Error::InvalidChar { offset: 5, byte: 0xC3 }.to_string(),
+        "invalid byte 0xC3 at offset 5"
+    );
+    assert_eq!(
+        Error::Overflow { offset: 0, byte: b'8' }.to_string(),
+        "value is too large: first character '8' is above '7'"
+    );
+    assert_eq!(Error::ValueTooLarge.to_string(), "value is too large for 128 bits");
+    assert_eq!(Error::InvalidZero.to_string(), "invalid zero value");
+}
+
+#[test]
+fn test_error_with_input() {
+    let input = "8000000000000000000000000U";
+    let error = input.parse::<Ulid>().unwrap_err();
+    assert_eq!(
+        error.with_input(input).to_string(),
+        "value is too large: first character '8' is above '7'\n8000000000000000000000000U\n^"
+    );
+
+    let input = "0123";
+    let error = input.parse::<Ulid>().unwrap_err();
+    assert_eq!(
+        error.with_input(input).to_string(),
+        "input is too short: expected 26 bytes, found 4\n0123\n    ^"
+    );
+
+    // Multi-byte characters and control characters
+    let input = "00\t000000000000000000000ä";
+    let error = input.parse::<Ulid>().unwrap_err();
+    assert_eq!(error, Error::InvalidChar { offset: 2, byte: b'\t' });
+    assert_eq!(
+        error.with_input(input).to_string(),
+        "invalid byte 0x09 at offset 2\n00?000000000000000000000ä\n  ^"
+    );
+
+    let input = "00000000000000000000000ä$";
+    let error = input.parse::<Ulid>().unwrap_err();
+    assert_eq!(error, Error::InvalidChar { offset: 23, byte: 0xC3 });
+    assert_eq!(
+        error.with_input(input).to_string(),
+        "invalid byte 0xC3 at offset 23\n00000000000000000000000ä$\n                       ^"
+    );
+
+    assert_eq!(Error::InvalidZero.with_input("0").to_string(), "invalid zero value");
+}
+
 // --- Serde ---
 
+#[cfg(feature = "uuid")]
+mod uuid_tests {
+    use ::uuid::Uuid;
+
+    use crate::*;
+
+    #[test]
+    fn test_uuid_conversions() {
+        let u = Ulid::new();
+        let uuid = Uuid::from(u);
+
+        assert_eq!(uuid.as_bytes(), &u.to_bytes());
+        assert_eq!(uuid.to_string(), u.to_uuid_string());
+        assert_eq!(Ulid::try_from(uuid), Ok(u));
+        assert_eq!(Ulid::from_uuid_str(&uuid.hyphenated().to_string()), Ok(u));
+
+        assert_eq!(Uuid::from(u.to_zeroable_ulid()), uuid);
+        assert_eq!(ZeroableUlid::from(uuid), u.to_zeroable_ulid());
+
+        assert_eq!(Ulid::try_from(Uuid::nil()), Err(Error::InvalidZero));
+        assert_eq!(ZeroableUlid::from(Uuid::nil()), ZeroableUlid::zeroed());
+        assert_eq!(Uuid::from(Ulid::MAX), Uuid::max());
+    }
+
+    #[test]
+    fn test_uuid_v7_conversions() {
+        let u = Ulid::new();
+        let uuid = Uuid::from_u128(u.to_uuid_v7());
+
+        assert_eq!(uuid.get_version_num(), 7);
+        assert_eq!(uuid.get_variant(), ::uuid::Variant::RFC4122);
+        assert_eq!(Ulid::from_uuid_v7(uuid.as_u128()).unwrap().timestamp(), u.timestamp());
+    }
+}
+
 #[cfg(feature = "serde")]
 mod serde_tests {
     use crate::*;
@@ -1038,6 +2191,63 @@ mod serde_tests {
         assert!(zu2.is_zero());
     }
 
+    #[test]
+    fn test_serde_ulid_string() {
+        let u = Ulid::new();
+        let json = serde_json::to_string(&u.encode()).unwrap();
+        assert_eq!(json, serde_json::to_string(&u).unwrap());
+
+        let s: UlidString = serde_json::from_str(&json).unwrap();
+        assert_eq!(s, u.encode());
+
+        assert!(serde_json::from_str::<UlidString>("\"invalid\"").is_err());
+    }
+
+    #[test]
+    fn test_serde_prefixed_ulid() {
+        struct Order;
+
+        impl UlidPrefix for Order {
+            const PREFIX: &'static str = "order";
+        }
+
+        let id = PrefixedUlid::<Order>::new();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, format!("\"order_{}\"", id.ulid()));
+
+        let id2: PrefixedUlid<Order> = serde_json::from_str(&json).unwrap();
+        assert_eq!(id2, id);
+
+        let error = serde_json::from_str::<PrefixedUlid<Order>>(&format!("\"user_{}\"", id.ulid())).unwrap_err();
+        assert!(error.to_string().contains("wrong prefix"));
+        assert!(serde_json::from_str::<PrefixedUlid<Order>>("42").is_err());
+    }
+
+    #[test]
+    fn test_serde_id() {
+        struct Entity;
+
+        let id = Id::<Entity>::new();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, serde_json::to_string(&id.ulid()).unwrap());
+
+        let id2: Id<Entity> = serde_json::from_str(&json).unwrap();
+        assert_eq!(id2, id);
+        assert!(serde_json::from_str::<Id<Entity>>("\"00000000000000000000000000\"").is_err());
+    }
+
+    #[test]
+    fn test_serde_type_id() {
+        // cspell:disable-next-line
+        let json = "\"prefix_01h455vb4pex5vsknk084sn02q\"";
+        let id: TypeId = serde_json::from_str(json).unwrap();
+        assert_eq!(id.uuid(), 0x0189_0A5D_AC96_774B_BCCE_B302_099A_8057);
+        assert_eq!(serde_json::to_string(&id).unwrap(), json);
+
+        assert!(serde_json::from_str::<TypeId>("\"PREFIX_00000000000000000000000000\"").is_err());
+        assert!(serde_json::from_str::<TypeId>("42").is_err());
+    }
+
     #[test]
     fn test_serde_ulid_zero_string_error() {
         // Deserializing a zero ULID string as Ulid should fail
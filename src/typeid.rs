@@ -0,0 +1,164 @@
+use core::{fmt, str::FromStr, str::from_utf8_unchecked};
+
+use crate::{
+    Error, TypeIdError, Ulid, base32,
+    prefixed::{MAX_LEN, MAX_PREFIX_LEN, encode_prefixed, is_valid_prefix},
+    util,
+};
+
+/// A [TypeID](https://github.com/jetify-com/typeid), a UUID with a type prefix, e.g. `user_01h455vb4pex5vsknk084sn02q`.
+///
+/// A TypeID consists of an optional prefix of up to 63 lowercase ASCII letters and underscores
+/// (starting and ending with a letter), an underscore separating prefix and suffix, and a suffix encoding
+/// the 128 bits of the UUID in 26 characters of lowercase Crockford Base32.
+///
+/// The suffix uses the same alphabet and bit layout as ULIDs, so a `Ulid` converts to a TypeID bit-for-bit:
+/// The suffix is just the lowercase ULID string. Unlike ULIDs, TypeIDs only accept lowercase suffixes
+/// and no aliases (`i`, `l`, `o`), rejecting them with [`Error::NonCanonicalChar`].
+///
+/// The TypeID specification expects the UUID to be a UUID v7, which a `Ulid` converted bit-for-bit is not.
+/// To create spec-conforming TypeIDs, convert with [`Ulid::to_uuid_v7()`] and [`TypeId::from_uuid()`] instead.
+///
+/// A `TypeId` stores its prefix inline, so it's `Copy` and needs no allocation.
+/// With the `serde` feature, it is serialized as its string form.
+///
+/// # Example
+///
+/// ```
+/// use mr_ulid::{TypeId, Ulid};
+///
+/// let u = Ulid::new();
+/// let id = TypeId::from_ulid("user", u)?;
+///
+/// assert_eq!(id.prefix(), "user");
+/// assert_eq!(id.to_string(), format!("user_{}", u.display_lower()));
+///
+/// let parsed: TypeId = id.to_string().parse()?;
+/// assert_eq!(parsed, id);
+/// assert_eq!(parsed.to_ulid(), Some(u));
+///
+/// // cspell:disable-next-line
+/// let id: TypeId = "prefix_01h455vb4pex5vsknk084sn02q".parse()?;
+/// assert_eq!(id.uuid(), 0x0189_0A5D_AC96_774B_BCCE_B302_099A_8057);
+/// # Ok::<(), mr_ulid::TypeIdError>(())
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TypeId {
+    prefix: [u8; MAX_PREFIX_LEN],
+    prefix_len: u8,
+    uuid: u128,
+}
+
+impl TypeId {
+    /// Creates a `TypeId` from a prefix and the 128 bits of a UUID.
+    ///
+    /// An empty prefix is allowed, and formats the TypeID without the separating underscore.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TypeIdError::InvalidPrefix`] if the prefix is invalid.
+    pub fn from_uuid(prefix: &str, uuid: u128) -> Result<Self, TypeIdError> {
+        if !prefix.is_empty() && !is_valid_prefix(prefix.as_bytes(), false) {
+            return Err(TypeIdError::InvalidPrefix);
+        }
+
+        let mut buffer = [0; MAX_PREFIX_LEN];
+        buffer[..prefix.len()].copy_from_slice(prefix.as_bytes());
+
+        Ok(Self {
+            prefix: buffer,
+            // The prefix is at most 63 bytes long
+            #[allow(clippy::cast_possible_truncation)]
+            prefix_len: prefix.len() as u8,
+            uuid,
+        })
+    }
+
+    /// Creates a `TypeId` from a prefix and a `Ulid`, converted bit-for-bit.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TypeIdError::InvalidPrefix`] if the prefix is invalid.
+    pub fn from_ulid(prefix: &str, ulid: Ulid) -> Result<Self, TypeIdError> {
+        Self::from_uuid(prefix, ulid.to_u128())
+    }
+
+    /// Returns the prefix, without the separating underscore. Empty if the TypeID has no prefix.
+    #[must_use]
+    pub fn prefix(&self) -> &str {
+        // Safety: Only valid prefixes are stored, which are ASCII (and so valid UTF-8)
+        unsafe { from_utf8_unchecked(&self.prefix[..usize::from(self.prefix_len)]) }
+    }
+
+    /// Returns the 128 bits of the UUID encoded by the suffix.
+    #[must_use]
+    pub const fn uuid(&self) -> u128 {
+        self.uuid
+    }
+
+    /// Converts the UUID to a `Ulid` bit-for-bit.
+    ///
+    /// Returns `None` if the UUID is the nil UUID, which is no valid ULID.
+    #[must_use]
+    pub const fn to_ulid(&self) -> Option<Ulid> {
+        Ulid::from_u128(self.uuid)
+    }
+
+    pub(crate) fn encode<'a>(&self, buffer: &'a mut [u8; MAX_LEN]) -> &'a str {
+        let mut suffix = [0; 26];
+        encode_prefixed(
+            &self.prefix[..usize::from(self.prefix_len)],
+            base32::encode_lower(self.uuid, &mut suffix),
+            buffer,
+        )
+    }
+}
+
+/// Decodes the suffix of a TypeID, accepting only lowercase characters without aliases.
+fn decode_suffix(suffix: &str) -> Result<u128, Error> {
+    let buffer = util::as_array(suffix.as_bytes())?;
+    let n = base32::decode(buffer)?;
+
+    if let Some(offset) = buffer.iter().position(|&c| !is_canonical_char(c)) {
+        let byte = buffer[offset];
+        return Err(Error::NonCanonicalChar { offset, byte });
+    }
+
+    Ok(n)
+}
+
+const fn is_canonical_char(c: u8) -> bool {
+    matches!(c, b'0'..=b'9' | b'a'..=b'h' | b'j' | b'k' | b'm' | b'n' | b'p'..=b't' | b'v'..=b'z')
+}
+
+impl fmt::Debug for TypeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = [0; MAX_LEN];
+        f.debug_tuple("TypeId").field(&self.encode(&mut buffer)).finish()
+    }
+}
+
+impl fmt::Display for TypeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = [0; MAX_LEN];
+        f.pad(self.encode(&mut buffer))
+    }
+}
+
+impl FromStr for TypeId {
+    type Err = TypeIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The suffix never contains underscores, so the prefix ends at the last one
+        let (prefix, suffix) = match s.rsplit_once('_') {
+            Some((prefix, suffix)) if is_valid_prefix(prefix.as_bytes(), false) => (prefix, suffix),
+            Some(_) => return Err(TypeIdError::InvalidPrefix),
+            None => ("", s),
+        };
+
+        let offset = s.len() - suffix.len();
+        let uuid = decode_suffix(suffix).map_err(|error| TypeIdError::InvalidSuffix(error.shifted(offset)))?;
+
+        Self::from_uuid(prefix, uuid)
+    }
+}
@@ -1,14 +1,25 @@
-use std::fmt::Formatter;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::fmt::{self, Formatter};
 
-use crate::{Error, RANDOM_BITS, RANDOM_MASK, TIMESTAMP_MAX, base32};
+use crate::{
+    Error, NANOS_PER_MILLI, RANDOM_BITS, RANDOM_MASK, SUB_MILLIS_BITS, SUB_MILLIS_RANDOM_BITS, TIMESTAMP_MASK,
+    TIMESTAMP_MAX, base32,
+};
 
 pub(crate) fn as_array<const N: usize>(bytes: &[u8]) -> Result<&[u8; N], Error> {
-    use std::cmp::Ordering;
+    use core::cmp::Ordering;
 
     match bytes.len().cmp(&N) {
         Ordering::Equal => Ok(bytes.try_into().unwrap()),
-        Ordering::Less => Err(Error::TooShort),
-        Ordering::Greater => Err(Error::TooLong),
+        Ordering::Less => Err(Error::TooShort {
+            expected: N,
+            actual: bytes.len(),
+        }),
+        Ordering::Greater => Err(Error::TooLong {
+            expected: N,
+            actual: bytes.len(),
+        }),
     }
 }
 
@@ -23,6 +34,42 @@ pub(crate) const fn from_parts(timestamp: u64, randomness: u128) -> Result<u128,
     }
 }
 
+pub(crate) const fn timestamp_nanos(ulid: u128) -> u128 {
+    let millis = ulid >> RANDOM_BITS;
+    let fraction = (ulid & RANDOM_MASK) >> SUB_MILLIS_RANDOM_BITS;
+
+    millis * NANOS_PER_MILLI + ((fraction * NANOS_PER_MILLI) >> SUB_MILLIS_BITS)
+}
+
+/// Version bits (`0111`) and variant bits (`10`) of a UUID v7.
+const UUID_V7_BITS: u128 = (0x7 << 76) | (0b10 << 62);
+const UUID_V7_BITS_MASK: u128 = (0xF << 76) | (0b11 << 62);
+
+/// The top 12 random bits of a ULID, which become `rand_a` of a UUID v7.
+const UUID_V7_RAND_A_MASK: u128 = 0xFFF << 68;
+/// The 6 random bits of a ULID dropped in a UUID v7.
+const UUID_V7_DROPPED_MASK: u128 = 0x3F << 62;
+/// The low 62 random bits of a ULID, which become `rand_b` of a UUID v7.
+const UUID_V7_RAND_B_MASK: u128 = (1 << 62) - 1;
+
+pub(crate) const fn to_uuid_v7(ulid: u128) -> u128 {
+    let rand_a = (ulid & UUID_V7_RAND_A_MASK) >> 4;
+    (ulid & TIMESTAMP_MASK) | UUID_V7_BITS | rand_a | (ulid & UUID_V7_RAND_B_MASK)
+}
+
+pub(crate) const fn from_uuid_v7(uuid: u128) -> Result<u128, Error> {
+    if uuid & UUID_V7_BITS_MASK != UUID_V7_BITS {
+        return Err(Error::NotUuidV7);
+    }
+    let rand_a = (uuid << 4) & UUID_V7_RAND_A_MASK;
+    Ok((uuid & TIMESTAMP_MASK) | rand_a | (uuid & UUID_V7_RAND_B_MASK))
+}
+
+pub(crate) const fn is_uuid_v7_lossless(ulid: u128) -> bool {
+    ulid & UUID_V7_DROPPED_MASK == 0
+}
+
+#[cfg(feature = "alloc")]
 pub(crate) fn try_to_string(ulid: u128) -> Option<String> {
     let mut s = String::new();
     s.try_reserve_exact(26).ok()?;
@@ -33,17 +80,28 @@ pub(crate) fn try_to_string(ulid: u128) -> Option<String> {
     Some(s)
 }
 
-pub(crate) fn debug_ulid(name: &str, ulid: u128, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+/// Formats a ULID string, honoring width, fill and alignment. The alternate flag (`{:#}`) selects lowercase.
+pub(crate) fn display_ulid(ulid: u128, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+    let mut buffer = [0; 26];
+
+    if f.alternate() {
+        f.pad(base32::encode_lower(ulid, &mut buffer))
+    } else {
+        f.pad(base32::encode(ulid, &mut buffer))
+    }
+}
+
+pub(crate) fn debug_ulid(name: &str, ulid: u128, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
     struct Timestamp(u64);
-    impl std::fmt::Debug for Timestamp {
-        fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-            write!(f, "\"{ts}\"", ts = timestamp_to_string(self.0))
+    impl fmt::Debug for Timestamp {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+            write!(f, "\"{ts}\"", ts = DateTime(self.0))
         }
     }
 
     struct Randomness(u128);
-    impl std::fmt::Debug for Randomness {
-        fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+    impl fmt::Debug for Randomness {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
             write!(f, "\"{:010X}\"", self.0)
         }
     }
@@ -61,65 +119,77 @@ pub(crate) fn debug_ulid(name: &str, ulid: u128, f: &mut Formatter<'_>) -> Resul
         .finish()
 }
 
-fn timestamp_to_string(millis: u64) -> String {
-    const DAYS_PER_YEAR: u64 = 365;
-    const DAYS_PER_LEAP_YEAR: u64 = DAYS_PER_YEAR + 1;
-
-    const DAYS_PER_QUAD_YEAR: u64 = 4 * DAYS_PER_YEAR + 1; // leap year: every 4 years,
-    const DAYS_PER_CENTURY: u64 = 25 * DAYS_PER_QUAD_YEAR - 1; // but not every 100 years,
-    const DAYS_PER_QUADRICENTENNIAL: u64 = 4 * DAYS_PER_CENTURY + 1; // but again every 400 years.
-
-    const BASE: u64 = 1600;
-    const DAYS_BASE_TO_1970: u64 = 3 * DAYS_PER_CENTURY + 1 + 70 * DAYS_PER_YEAR + 70 / 4;
-
-    let (seconds, millis) = (millis / 1000, (millis % 1000) as u32);
-    let (minutes, seconds) = (seconds / 60, (seconds % 60) as u32);
-    let (hours, minutes) = (minutes / 60, (minutes % 60) as u32);
-    let (days, hours) = (hours / 24, (hours % 24) as u32);
-
-    // days relative to year 1600
-    let days = days + DAYS_BASE_TO_1970;
-
-    let (quadricentennials, days) = (days / DAYS_PER_QUADRICENTENNIAL, days % DAYS_PER_QUADRICENTENNIAL);
-    let (centuries, days) = (days / DAYS_PER_CENTURY, days % DAYS_PER_CENTURY);
-    let (quad_years, days) = (days / DAYS_PER_QUAD_YEAR, days % DAYS_PER_QUAD_YEAR);
+/// Formats a millisecond timestamp as an ISO 8601 date and time, without allocating.
+struct DateTime(u64);
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        const DAYS_PER_YEAR: u64 = 365;
+        const DAYS_PER_LEAP_YEAR: u64 = DAYS_PER_YEAR + 1;
+
+        const DAYS_PER_QUAD_YEAR: u64 = 4 * DAYS_PER_YEAR + 1; // leap year: every 4 years,
+        const DAYS_PER_CENTURY: u64 = 25 * DAYS_PER_QUAD_YEAR - 1; // but not every 100 years,
+        const DAYS_PER_QUADRICENTENNIAL: u64 = 4 * DAYS_PER_CENTURY + 1; // but again every 400 years.
+
+        const BASE: u64 = 1600;
+        const DAYS_BASE_TO_1970: u64 = 3 * DAYS_PER_CENTURY + 1 + 70 * DAYS_PER_YEAR + 70 / 4;
+
+        let (seconds, millis) = (self.0 / 1000, (self.0 % 1000) as u32);
+        let (minutes, seconds) = (seconds / 60, (seconds % 60) as u32);
+        let (hours, minutes) = (minutes / 60, (minutes % 60) as u32);
+        let (days, hours) = (hours / 24, (hours % 24) as u32);
+
+        // days relative to year 1600
+        let days = days + DAYS_BASE_TO_1970;
+
+        let (quadricentennials, days) = (days / DAYS_PER_QUADRICENTENNIAL, days % DAYS_PER_QUADRICENTENNIAL);
+        let (centuries, days) = (days / DAYS_PER_CENTURY, days % DAYS_PER_CENTURY);
+        let (quad_years, days) = (days / DAYS_PER_QUAD_YEAR, days % DAYS_PER_QUAD_YEAR);
+
+        let is_leap_year = days < DAYS_PER_LEAP_YEAR;
+
+        let (years, days) = if is_leap_year {
+            (0, days)
+        } else {
+            let days = days - DAYS_PER_LEAP_YEAR;
+            let (normal_years, days) = (days / DAYS_PER_YEAR, days % DAYS_PER_YEAR);
+            (normal_years + 1, days)
+        };
+
+        let year = BASE + quadricentennials * 400 + centuries * 100 + quad_years * 4 + years;
+
+        #[rustfmt::skip]
+        let days_in_month = [
+            31,
+            if is_leap_year { 29 } else { 28 },
+            31, 30, 31, 30, 31, 31, 30, 31, 30, 31,
+        ];
+
+        let mut days = days;
+        let mut month = 0;
+        while days >= days_in_month[month] {
+            days -= days_in_month[month];
+            month += 1;
+        }
 
-    let is_leap_year = days < DAYS_PER_LEAP_YEAR;
+        let month = month + 1;
+        let day = days + 1;
 
-    let (years, days) = if is_leap_year {
-        (0, days)
-    } else {
-        let days = days - DAYS_PER_LEAP_YEAR;
-        let (normal_years, days) = (days / DAYS_PER_YEAR, days % DAYS_PER_YEAR);
-        (normal_years + 1, days)
-    };
-
-    let year = BASE + quadricentennials * 400 + centuries * 100 + quad_years * 4 + years;
-
-    #[rustfmt::skip]
-    let days_in_month = [
-        31,
-        if is_leap_year { 29 } else { 28 },
-        31, 30, 31, 30, 31, 31, 30, 31, 30, 31,
-    ];
-
-    let mut days = days;
-    let mut month = 0;
-    while days >= days_in_month[month] {
-        days -= days_in_month[month];
-        month += 1;
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{hours:02}:{minutes:02}:{seconds:02}.{millis:03}Z"
+        )
     }
-
-    let month = month + 1;
-    let day = days + 1;
-
-    format!("{year:04}-{month:02}-{day:02}T{hours:02}:{minutes:02}:{seconds:02}.{millis:03}Z")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn timestamp_to_string(millis: u64) -> String {
+        DateTime(millis).to_string()
+    }
+
     #[test]
     fn test_timestamp_to_string() {
         assert_eq!(timestamp_to_string(0), "1970-01-01T00:00:00.000Z");
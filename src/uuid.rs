@@ -0,0 +1,28 @@
+use uuid::Uuid;
+
+use crate::{Error, Ulid, ZeroableUlid};
+
+impl From<Ulid> for Uuid {
+    fn from(ulid: Ulid) -> Self {
+        Self::from_u128(ulid.to_u128())
+    }
+}
+
+impl TryFrom<Uuid> for Ulid {
+    type Error = Error;
+    fn try_from(uuid: Uuid) -> Result<Self, Self::Error> {
+        Self::from_u128(uuid.as_u128()).ok_or(Error::InvalidZero)
+    }
+}
+
+impl From<ZeroableUlid> for Uuid {
+    fn from(ulid: ZeroableUlid) -> Self {
+        Self::from_u128(ulid.to_u128())
+    }
+}
+
+impl From<Uuid> for ZeroableUlid {
+    fn from(uuid: Uuid) -> Self {
+        Self::from_u128(uuid.as_u128())
+    }
+}
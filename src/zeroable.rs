@@ -1,10 +1,15 @@
-use std::{
-    fmt,
-    str::FromStr,
-    time::{Duration, SystemTime},
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::{fmt, str::FromStr};
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    DisplayLower, Error, ParseOptions, RANDOM_BITS, RANDOM_MASK, Ulid, UlidString, base32, compact, hex, human, parse,
+    util,
 };
-
-use crate::{Error, RANDOM_BITS, RANDOM_MASK, Ulid, base32, generator, util};
+#[cfg(any(feature = "std", feature = "critical-section"))]
+use crate::{GenerateError, generator};
 
 /// A ULID with even the value zero allowed.
 ///
@@ -109,9 +114,10 @@ impl ZeroableUlid {
     ///
     /// assert!((t1 < t2) || (t1 == t2 && r1 < r2));
     /// ```
+    #[cfg(any(feature = "std", feature = "critical-section"))]
     #[must_use]
     pub fn new() -> Self {
-        Self(generator::generate().unwrap())
+        Self::try_generate().unwrap_or_else(|error| panic!("failed to generate ULID: {error}"))
     }
 
     /// Tests if a `ZeroableUlid` is zero.
@@ -153,6 +159,27 @@ impl ZeroableUlid {
         (self.0 >> RANDOM_BITS) as u64
     }
 
+    /// Returns the timestamp of a `ZeroableUlid` in nanoseconds since the Unix epoch.
+    ///
+    /// This is only meaningful for ULIDs generated with sub-millisecond precision
+    /// (see [`set_sub_millisecond_precision()`](crate::set_sub_millisecond_precision)), where the top 12 bits
+    /// of the randomness store the fraction of the millisecond. The result has a resolution of about 244 ns.
+    /// For other ULIDs, the sub-millisecond part is just random.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_parts(1, 0x800 << 68).unwrap();
+    ///
+    /// assert_eq!(u.timestamp_nanos(), 1_500_000);
+    /// ```
+    #[must_use]
+    pub const fn timestamp_nanos(self) -> u128 {
+        util::timestamp_nanos(self.0)
+    }
+
     /// Returns the random part of a `ZeroableUlid`.
     ///
     /// The randomness of a `ZeroableUlid` is limited to 80 bits.
@@ -192,6 +219,7 @@ impl ZeroableUlid {
     ///
     /// assert!(u.datetime() <= SystemTime::now());
     /// ```
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn datetime(self) -> SystemTime {
         SystemTime::UNIX_EPOCH + Duration::from_millis(self.timestamp())
@@ -351,6 +379,369 @@ impl ZeroableUlid {
         self.0
     }
 
+    /// Converts a `ZeroableUlid` into the hyphenated 8-4-4-4-12 hex form of UUIDs, in lowercase.
+    ///
+    /// The UUID has the same bytes as [`ZeroableUlid::to_bytes()`], so the ULID can be stored in a UUID column
+    /// and read back with [`ZeroableUlid::from_uuid_str()`]. The UUID is no valid RFC 9562 UUID, as the
+    /// version and variant bits are part of the ULID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    ///
+    /// assert_eq!(u.to_uuid_string(), "0192fa64-e41e-5b4a-45e0-3fd8f28b7522");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_uuid_string(self) -> String {
+        let mut buffer = [0; 36];
+        String::from(hex::encode(self.0, &mut buffer))
+    }
+
+    /// Parses a `ZeroableUlid` from the hyphenated 8-4-4-4-12 hex form of UUIDs.
+    ///
+    /// Hex digits may be upper- or lowercase. Braces, a `urn:uuid:` prefix, or the simple form without
+    /// hyphens are not accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't a hyphenated UUID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_uuid_str("0192FA64-E41E-5B4A-45E0-3FD8F28B7522")?;
+    ///
+    /// assert_eq!(u.to_u128(), 0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    /// # Ok::<(), mr_ulid::Error>(())
+    /// ```
+    pub fn from_uuid_str(s: &str) -> Result<Self, Error> {
+        Ok(Self::from_u128(hex::decode(s.as_bytes())?))
+    }
+
+    /// Converts a `ZeroableUlid` into 22 characters of Base58, using Bitcoin's alphabet.
+    ///
+    /// The alphabet omits the look-alikes `0`, `O`, `I` and `l`, and is in ASCII order. The string has
+    /// a fixed length with leading `1`s (the zero digit), so strings sort like the ULIDs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.to_base58_string(), "1CGu1BjU9Rr9h2ZVm6P3oo");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_base58_string(self) -> String {
+        let mut buffer = [0; compact::LEN];
+        String::from(compact::encode(&compact::BASE58, self.0, &mut buffer))
+    }
+
+    /// Parses a `ZeroableUlid` from 22 characters of Base58, as created by [`ZeroableUlid::to_base58_string()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't 22 characters of Base58,
+    /// or [`Error::ValueTooLarge`] if it represents a value larger than 128 bits.
+    pub fn from_base58_str(s: &str) -> Result<Self, Error> {
+        Ok(Self::from_u128(compact::decode(&compact::BASE58, s.as_bytes())?))
+    }
+
+    /// Converts a `ZeroableUlid` into 22 characters of Base62, using digits, uppercase and lowercase letters.
+    ///
+    /// The alphabet is in ASCII order, and the string has a fixed length with leading `0`s,
+    /// so strings sort like the ULIDs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.to_base62_string(), "02yA0piThDHxaCD6tFIMHi");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_base62_string(self) -> String {
+        let mut buffer = [0; compact::LEN];
+        String::from(compact::encode(&compact::BASE62, self.0, &mut buffer))
+    }
+
+    /// Parses a `ZeroableUlid` from 22 characters of Base62, as created by [`ZeroableUlid::to_base62_string()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't 22 characters of Base62,
+    /// or [`Error::ValueTooLarge`] if it represents a value larger than 128 bits.
+    pub fn from_base62_str(s: &str) -> Result<Self, Error> {
+        Ok(Self::from_u128(compact::decode(&compact::BASE62, s.as_bytes())?))
+    }
+
+    /// Converts a `ZeroableUlid` into 22 characters of unpadded base64url (RFC 4648), encoding the bytes of [`ZeroableUlid::to_bytes()`].
+    ///
+    /// Other base64url decoders read the string as the 16 bytes of the ULID. Unlike Base58 and Base62,
+    /// the alphabet is not in ASCII order, so the strings don't sort like the ULIDs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.to_base64url_string(), "AZL6ZOQeW0pF4D_Y8ot1Ig");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_base64url_string(self) -> String {
+        let mut buffer = [0; compact::LEN];
+        String::from(compact::encode_base64url(self.0, &mut buffer))
+    }
+
+    /// Parses a `ZeroableUlid` from 22 characters of unpadded base64url (RFC 4648), as created by [`ZeroableUlid::to_base64url_string()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't 22 characters of base64url, or if the unused low 4 bits of the last
+    /// character aren't zero.
+    pub fn from_base64url_str(s: &str) -> Result<Self, Error> {
+        Ok(Self::from_u128(compact::decode_base64url(s.as_bytes())?))
+    }
+
+    /// Converts a `ZeroableUlid` into a form for reading aloud and typing by hand: Groups of 5 characters,
+    /// separated by hyphens, followed by a Crockford check symbol.
+    ///
+    /// The check symbol is the value modulo 37, so every single mistyped character is detected
+    /// by [`ZeroableUlid::from_human_str()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.to_human_string(), "01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2D");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn to_human_string(self) -> String {
+        let mut buffer = [0; human::LEN];
+        String::from(human::encode(self.0, &mut buffer))
+    }
+
+    /// Parses a `ZeroableUlid` from the form created by [`ZeroableUlid::to_human_string()`], verifying the check symbol.
+    ///
+    /// Hyphens are ignored anywhere, and letters are case-insensitive.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't a ULID followed by a check symbol.
+    /// Returns [`Error::ChecksumMismatch`] if the check symbol doesn't match, with the likely typo
+    /// if a single correction between easily confused characters fixes it; see [`typo_candidates()`](crate::typo_candidates)
+    /// for all such corrections.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::{Error, ZeroableUlid};
+    ///
+    // cspell:disable-next-line
+    /// let u = ZeroableUlid::from_human_str("01jbx-69s0y-bd54b-r1zv3-s8px9-2d")?;
+    /// assert_eq!(u.to_u128(), 0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    ///
+    // cspell:disable-next-line
+    /// let error = ZeroableUlid::from_human_str("01JBX-69S0Y-BD54B-R1ZV3-S8PX9-2E").unwrap_err();
+    /// assert!(matches!(error, Error::ChecksumMismatch { .. }));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn from_human_str(s: &str) -> Result<Self, Error> {
+        Ok(Self::from_u128(human::decode(s)?))
+    }
+
+    /// Converts a `ZeroableUlid` into a UUID v7 (as `u128`, like [`ZeroableUlid::to_u128()`]).
+    ///
+    /// The 48-bit timestamp is preserved exactly. A UUID v7 has only 74 random bits, as it reserves 6 bits
+    /// for version and variant. The top 12 random bits of the ULID become `rand_a` (which holds the fraction
+    /// of the millisecond with [`set_sub_millisecond_precision()`](crate::set_sub_millisecond_precision),
+    /// like RFC 9562 method 3), and the low 62 random bits become `rand_b`, so incremented ULIDs stay distinct.
+    /// The 6 random bits in between are lost; [`ZeroableUlid::is_uuid_v7_lossless()`] tells if they are all zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::new();
+    /// let uuid = u.to_uuid_v7();
+    ///
+    /// assert_eq!(uuid >> 80, u.to_u128() >> 80); // same timestamp
+    /// assert_eq!((uuid >> 76) & 0xF, 7); // version 7
+    /// ```
+    #[must_use]
+    pub const fn to_uuid_v7(self) -> u128 {
+        util::to_uuid_v7(self.0)
+    }
+
+    /// Converts a UUID v7 (as `u128`, like [`ZeroableUlid::from_u128()`]) into a `ZeroableUlid`.
+    ///
+    /// The inverse of [`ZeroableUlid::to_uuid_v7()`]: The version and variant bits are removed, and the 6 random
+    /// bits lost in the conversion to UUID v7 are zero. So converting a UUID v7 into a ULID and back is
+    /// always lossless, and keeps the time order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotUuidV7`] if the version or variant bits are not the ones of a UUID v7.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let uuid = 0x0192_FA64_E41E_7B4A_85E0_3FD8_F28B_7522;
+    /// let u = ZeroableUlid::from_uuid_v7(uuid)?;
+    ///
+    /// assert_eq!(u.timestamp(), 0x0192_FA64_E41E);
+    /// assert_eq!(u.to_uuid_v7(), uuid);
+    /// # Ok::<(), mr_ulid::Error>(())
+    /// ```
+    pub const fn from_uuid_v7(uuid: u128) -> Result<Self, Error> {
+        match util::from_uuid_v7(uuid) {
+            Ok(n) => Ok(Self::from_u128(n)),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns `true` if [`ZeroableUlid::to_uuid_v7()`] is lossless for this `ZeroableUlid`, i.e. converting back with
+    /// [`ZeroableUlid::from_uuid_v7()`] yields the same `ZeroableUlid`.
+    ///
+    /// This is the case if the 6 random bits below the top 12 random bits are all zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::new();
+    ///
+    /// assert_eq!(u.is_uuid_v7_lossless(), ZeroableUlid::from_uuid_v7(u.to_uuid_v7()) == Ok(u));
+    /// ```
+    #[must_use]
+    pub const fn is_uuid_v7_lossless(self) -> bool {
+        util::is_uuid_v7_lossless(self.0)
+    }
+
+    /// Returns an adaptor displaying the `ZeroableUlid` in lowercase, like formatting with `{:#}`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_u128(0x0192_FA64_E41E_5B4A_45E0_3FD8_F28B_7522);
+    ///
+    // cspell:disable-next-line
+    /// assert_eq!(u.display_lower().to_string(), "01jbx69s0ybd54br1zv3s8px92");
+    /// assert_eq!(format!("{u:#}"), u.display_lower().to_string());
+    /// ```
+    #[must_use]
+    pub const fn display_lower(self) -> DisplayLower {
+        DisplayLower::new(self.0)
+    }
+
+    /// Encodes a `ZeroableUlid` into its canonical string form, without allocation.
+    ///
+    /// The returned [`UlidString`] is stored inline and dereferences to [`str`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_u128(42);
+    ///
+    /// assert_eq!(u.encode(), "0000000000000000000000001A");
+    /// assert_eq!(u.encode().as_str(), u.to_string());
+    /// ```
+    #[must_use]
+    pub fn encode(self) -> UlidString {
+        UlidString::new(self.0)
+    }
+
+    /// Encodes a `ZeroableUlid` into its canonical string form into the given buffer, without allocation.
+    ///
+    /// Returns the encoded string, borrowed from the buffer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_u128(42);
+    /// let mut buffer = [0; 26];
+    ///
+    /// assert_eq!(u.encode_into(&mut buffer), "0000000000000000000000001A");
+    /// ```
+    pub fn encode_into(self, buffer: &mut [u8; 26]) -> &str {
+        base32::encode(self.0, buffer)
+    }
+
+    /// Decodes a `ZeroableUlid` from the 26 ASCII bytes of its string form, like parsing with [`FromStr`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are no valid ULID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::ZeroableUlid;
+    ///
+    /// let u = ZeroableUlid::from_ascii(b"0000000000000000000000001A")?;
+    ///
+    /// assert_eq!(u.to_u128(), 42);
+    /// # Ok::<(), mr_ulid::Error>(())
+    /// ```
+    pub fn from_ascii(bytes: &[u8; 26]) -> Result<Self, Error> {
+        Ok(Self::from_u128(base32::decode(bytes)?))
+    }
+
+    /// Parses a `ZeroableUlid` from a string, with options controlling how strict the parser is.
+    ///
+    /// With [`ParseOptions::DEFAULT`], this is the same as parsing with [`FromStr`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not a valid ULID according to `options`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::{ParseOptions, ZeroableUlid};
+    ///
+    /// let u = ZeroableUlid::parse_with("urn:ulid:00000000000000000000000000", ParseOptions::LENIENT)?;
+    /// assert!(u.is_zero());
+    ///
+    /// assert!(ZeroableUlid::parse_with("oooooooooooooooooooooooooo", ParseOptions::STRICT).is_err());
+    /// # Ok::<(), mr_ulid::Error>(())
+    /// ```
+    pub fn parse_with(s: &str, options: ParseOptions) -> Result<Self, Error> {
+        Ok(Self::from_u128(parse::parse(s, options)?))
+    }
+
     /// Creates a `ZeroableUlid` from a `u128` integer.
     ///
     ///
@@ -389,9 +780,35 @@ impl ZeroableUlid {
     /// assert!(u1.timestamp() <= u2.timestamp());
     /// # Some(()) }}
     /// ```
+    #[cfg(any(feature = "std", feature = "critical-section"))]
     #[must_use]
     pub fn try_new() -> Option<Self> {
-        Some(Self(generator::generate()?))
+        Self::try_generate().ok()
+    }
+
+    /// Generates a new `ZeroableUlid`, reporting why generation failed.
+    ///
+    /// This is a variant of [`ZeroableUlid::try_new()`], which tells the cause of a failure,
+    /// e.g. to distinguish a misconfigured entropy source from a broken clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the case of problems with the ULID-generator (see [`GenerateError`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mr_ulid::{GenerateError, ZeroableUlid};
+    ///
+    /// match ZeroableUlid::try_generate() {
+    ///     Ok(u) => println!("generated {u}"),
+    ///     Err(GenerateError::ClockBeforeEpoch) => eprintln!("check the system clock"),
+    ///     Err(error) => eprintln!("failed to generate ULID: {error}"),
+    /// }
+    /// ```
+    #[cfg(any(feature = "std", feature = "critical-section"))]
+    pub fn try_generate() -> Result<Self, GenerateError> {
+        generator::generate().map(Self)
     }
 
     /// Returns the timestamp part of a `ZeroableUlid` as a [`SystemTime`] and never panics.
@@ -410,6 +827,7 @@ impl ZeroableUlid {
     ///
     /// let datetime: Option<SystemTime> = u.try_datetime();
     /// ```
+    #[cfg(feature = "std")]
     #[must_use]
     pub fn try_datetime(self) -> Option<SystemTime> {
         SystemTime::UNIX_EPOCH.checked_add(Duration::from_millis(self.timestamp()))
@@ -420,6 +838,7 @@ impl ZeroableUlid {
     /// While the blanket implementation of [`std::string::ToString`] for `std::fmt::Display` may
     /// panic, this method is guaranteed to never panic, but returns `None` if the string representation cannot be created.
     /// One reason this can happen is if the allocation of memory for the string fails.
+    #[cfg(feature = "alloc")]
     #[must_use]
     pub fn try_to_string(self) -> Option<String> {
         util::try_to_string(self.0)
@@ -446,9 +865,27 @@ impl fmt::Debug for ZeroableUlid {
 }
 
 impl fmt::Display for ZeroableUlid {
+    /// Formats the ULID string, honoring width, fill and alignment. The alternate flag (`{:#}`) selects lowercase.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        util::display_ulid(self.0, f)
+    }
+}
+
+impl fmt::LowerHex for ZeroableUlid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for ZeroableUlid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Binary for ZeroableUlid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut buffer = [0; 26];
-        f.write_str(base32::encode(self.0, &mut buffer))
+        fmt::Binary::fmt(&self.0, f)
     }
 }
 
@@ -456,7 +893,7 @@ impl FromStr for ZeroableUlid {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let buffer = util::as_array(s.as_bytes())?;
-        Ok(Self::from_u128(base32::decode(buffer)?))
+        Self::from_ascii(buffer)
     }
 }
 